@@ -1,5 +1,9 @@
+use std::collections::HashMap;
+
 use image::GenericImageView;
 
+use crate::state::core::food::FoodKind;
+
 pub struct SpriteFrame {
     pub width: u32,  // Width of the sprite in pixels
     pub height: u32, // Height of the sprite in pixels
@@ -14,7 +18,8 @@ impl SpriteFrame {
 
 pub struct SpriteMaps {
     pub body: Vec<SpriteFrame>,
-    pub food: Vec<SpriteFrame>,
+    pub food: Vec<SpriteFrame>, // Standard food
+    pub food_kinds: HashMap<FoodKind, Vec<SpriteFrame>>, // Every other kind of food
     pub head: Vec<SpriteFrame>,
     pub tail: Vec<SpriteFrame>,
     pub game_over_screen: Vec<SpriteFrame>,
@@ -23,7 +28,14 @@ pub struct SpriteMaps {
     pub blue_strip: Vec<SpriteFrame>,
     pub powerups: Vec<SpriteFrame>,
     pub choose_powerup: Vec<SpriteFrame>,
-    pub loot_crate : Vec<SpriteFrame>
+    pub loot_crate : Vec<SpriteFrame>,
+    pub obstacles: Vec<SpriteFrame>
+}
+
+impl Default for SpriteMaps {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SpriteMaps {
@@ -31,6 +43,7 @@ impl SpriteMaps {
         Self {
             body: load_sprites_from_map("assets/sprites/body.png", 6, 6),
             food: load_sprites_from_map("assets/sprites/food.png", 16, 16),
+            food_kinds: load_food_kind_sprites(),
             head: load_sprites_from_map("assets/sprites/head.png", 16, 16),
             tail: load_sprites_from_map("assets/sprites/tail.png", 6, 6),
             game_over_screen: load_sprites_from_map("assets/sprites/game_over.png", 256, 224),
@@ -39,7 +52,29 @@ impl SpriteMaps {
             blue_strip: load_sprites_from_map("assets/sprites/blue_strip.png", 256, 224),
             powerups: load_sprites_from_map("assets/sprites/powerups.png", 128, 112),
             choose_powerup: load_sprites_from_map("assets/sprites/powerup_grid.png", 256, 112),
-            loot_crate: load_sprites_from_map("assets/sprites/loot_crate.png", 128, 112)
+            loot_crate: load_sprites_from_map("assets/sprites/loot_crate.png", 128, 112),
+            obstacles: load_sprites_from_map("assets/sprites/obstacles.png", 6, 8)
+        }
+    }
+}
+
+/// Special food kinds share one sheet, two frames per row in the order golden, rotten, spicy,
+/// cosmic.
+fn load_food_kind_sprites() -> HashMap<FoodKind, Vec<SpriteFrame>> {
+    let mut frames = load_sprites_from_map("assets/sprites/food_kinds.png", 16, 16).into_iter();
+    [FoodKind::Golden, FoodKind::Rotten, FoodKind::Spicy, FoodKind::Cosmic]
+        .into_iter()
+        .map(|kind| (kind, frames.by_ref().take(2).collect()))
+        .collect()
+}
+
+impl SpriteMaps {
+    /// Animation frames for food of `kind`. Kinds whose frames have not been loaded yet borrow the
+    /// standard food's.
+    pub fn food_frames(&self, kind: FoodKind) -> &[SpriteFrame] {
+        match self.food_kinds.get(&kind) {
+            Some(frames) if !frames.is_empty() => frames,
+            _ => &self.food,
         }
     }
 }
@@ -58,7 +93,7 @@ impl SpriteMaps {
 /// A vector containing tuples of sprite dimensions and pixel data.
 pub fn load_sprites_from_map(sprite_map_path: &str, sprite_width: u32, sprite_height: u32) -> Vec<SpriteFrame> {
     // Load the sprite map image
-    let sprite_map = image::open(sprite_map_path).expect(&format!("Failed to open sprite map at {}", sprite_map_path));
+    let sprite_map = image::open(sprite_map_path).unwrap_or_else(|_| panic!("Failed to open sprite map at {}", sprite_map_path));
     let (map_width, map_height) = sprite_map.dimensions();
 
     // Calculate the number of sprites in each dimension
@@ -118,7 +153,7 @@ pub fn img_to_buffer(img: &image::DynamicImage) -> Vec<u32> {
 /// - An alpha value of 255 (0xFF) means the pixel is completely opaque.
 ///
 /// The formula for alpha blending is:
-/// ```
+/// ```text
 ///  blended_color = (foreground_color * alpha + background_color * (255 - alpha)) / 255
 /// ```
 ///
@@ -153,20 +188,7 @@ pub fn draw_sprite(
                 // Apply darkening if specified
                 maybe_darken(&mut sprite_pixel, darkness_factor);
 
-                let sprite_alpha = (sprite_pixel >> 24) & 0xFF;
-                let sprite_rgb = sprite_pixel & 0x00FFFFFF;
-
-                if sprite_alpha > 0 {
-                    let window_pixel = window_buffer[window_pixel_index];
-                    let window_rgb = window_pixel & 0x00FFFFFF;
-
-                    let blended_r = ((sprite_rgb >> 16) & 0xFF) * sprite_alpha / 255 + ((window_rgb >> 16) & 0xFF) * (255 - sprite_alpha) / 255;
-                    let blended_g = ((sprite_rgb >> 8) & 0xFF) * sprite_alpha / 255 + ((window_rgb >> 8) & 0xFF) * (255 - sprite_alpha) / 255;
-                    let blended_b = (sprite_rgb & 0xFF) * sprite_alpha / 255 + (window_rgb & 0xFF) * (255 - sprite_alpha) / 255;
-
-                    let blended_pixel = 0xFF000000 | (blended_r & 0xFF) << 16 | (blended_g & 0xFF) << 8 | (blended_b & 0xFF);
-                    window_buffer[window_pixel_index] = blended_pixel;
-                }
+                blend_pixel(&mut window_buffer[window_pixel_index], sprite_pixel);
             }
         }
     }
@@ -218,25 +240,63 @@ where
                 // Apply darkening if factor is provided
                 maybe_darken(&mut sprite_pixel, darkness_factor);
 
-                let sprite_alpha = (sprite_pixel >> 24) & 0xFF;
-                let sprite_rgb = sprite_pixel & 0x00FFFFFF;
+                blend_pixel(&mut window_buffer[window_pixel_index], sprite_pixel);
+            }
+        }
+    }
+}
+
+/// Draws a sprite resized to `size` (width, height), picking the nearest source pixel for each
+/// target pixel. Blending and darkening work the same as in [`draw_sprite`].
+pub fn draw_sprite_scaled(
+    x: usize,
+    y: usize,
+    size: (usize, usize),
+    sprite: &SpriteFrame,
+    window_buffer: &mut [u32],
+    window_width: usize,
+    darkness_factor: Option<f32>
+) {
+    let (width, height) = size;
+    for row in 0..height {
+        for col in 0..width {
+            // Stay inside the buffer's rows instead of wrapping onto the next one
+            if x + col >= window_width {
+                break;
+            }
+            let window_pixel_index = (y + row) * window_width + (x + col);
+            if window_pixel_index >= window_buffer.len() {
+                return;
+            }
 
-                if sprite_alpha > 0 {
-                    let window_pixel = window_buffer[window_pixel_index];
-                    let window_rgb = window_pixel & 0x00FFFFFF;
+            let sprite_col = col * sprite.width as usize / width;
+            let sprite_row = row * sprite.height as usize / height;
+            let mut sprite_pixel = sprite.data[sprite_row * (sprite.width as usize) + sprite_col];
 
-                    let blended_r = ((sprite_rgb >> 16) & 0xFF) * sprite_alpha / 255 + ((window_rgb >> 16) & 0xFF) * (255 - sprite_alpha) / 255;
-                    let blended_g = ((sprite_rgb >> 8) & 0xFF) * sprite_alpha / 255 + ((window_rgb >> 8) & 0xFF) * (255 - sprite_alpha) / 255;
-                    let blended_b = (sprite_rgb & 0xFF) * sprite_alpha / 255 + (window_rgb & 0xFF) * (255 - sprite_alpha) / 255;
+            // Apply darkening if specified
+            maybe_darken(&mut sprite_pixel, darkness_factor);
 
-                    let blended_pixel = 0xFF000000 | (blended_r & 0xFF) << 16 | (blended_g & 0xFF) << 8 | (blended_b & 0xFF);
-                    window_buffer[window_pixel_index] = blended_pixel;
-                }
-            }
+            blend_pixel(&mut window_buffer[window_pixel_index], sprite_pixel);
         }
     }
 }
 
+/// Alpha-blends `sprite_pixel` over `window_pixel`; fully transparent pixels leave it untouched.
+fn blend_pixel(window_pixel: &mut u32, sprite_pixel: u32) {
+    let sprite_alpha = (sprite_pixel >> 24) & 0xFF;
+    if sprite_alpha == 0 {
+        return;
+    }
+    let sprite_rgb = sprite_pixel & 0x00FFFFFF;
+    let window_rgb = *window_pixel & 0x00FFFFFF;
+
+    let blended_r = ((sprite_rgb >> 16) & 0xFF) * sprite_alpha / 255 + ((window_rgb >> 16) & 0xFF) * (255 - sprite_alpha) / 255;
+    let blended_g = ((sprite_rgb >> 8) & 0xFF) * sprite_alpha / 255 + ((window_rgb >> 8) & 0xFF) * (255 - sprite_alpha) / 255;
+    let blended_b = (sprite_rgb & 0xFF) * sprite_alpha / 255 + (window_rgb & 0xFF) * (255 - sprite_alpha) / 255;
+
+    *window_pixel = 0xFF000000 | (blended_r & 0xFF) << 16 | (blended_g & 0xFF) << 8 | (blended_b & 0xFF);
+}
+
 /// Applies a darkness factor to a sprite pixel iff `darkness_factor` is set.
 ///
 /// # Parameters
@@ -248,7 +308,6 @@ where
 /// factor to each channel, and then reconstructs the pixel in ARGB format. The alpha channel
 /// remains unchanged.
 fn maybe_darken(sprite_pixel: &mut u32, darkness_factor: Option<f32>) {
-
     if let Some(factor) = darkness_factor {
         let alpha = (*sprite_pixel >> 24) & 0xFF;
         let r = ((*sprite_pixel >> 16) & 0xFF) as f32 * factor;
@@ -295,6 +354,17 @@ pub fn add_food_sprite(sprites: &mut SpriteMaps, width: u32, height: u32, data:
     Ok(())
 }
 
+pub fn add_food_kind_sprite(sprites: &mut SpriteMaps, kind: FoodKind, width: u32, height: u32, data: Vec<u32>) -> Result<(), wasm_bindgen::JsValue> {
+    let sprite_frame = SpriteFrame {
+        width,
+        height,
+        data,
+    };
+
+    sprites.food_kinds.entry(kind).or_default().push(sprite_frame);
+    Ok(())
+}
+
 pub fn add_tail_sprite(sprites: &mut SpriteMaps, width: u32, height: u32, data: Vec<u32>) -> Result<(), wasm_bindgen::JsValue> {
     let sprite_frame = SpriteFrame {
         width,
@@ -370,4 +440,15 @@ pub fn add_loot_crate_sprite(sprites: &mut SpriteMaps, width: u32, height: u32,
 
     sprites.loot_crate.push(sprite_frame);
     Ok(())
-}
\ No newline at end of file
+}
+
+pub fn add_obstacle_sprite(sprites: &mut SpriteMaps, width: u32, height: u32, data: Vec<u32>) -> Result<(), wasm_bindgen::JsValue> {
+    let sprite_frame = SpriteFrame {
+        width,
+        height,
+        data,
+    };
+
+    sprites.obstacles.push(sprite_frame);
+    Ok(())
+}
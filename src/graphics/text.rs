@@ -20,12 +20,13 @@ impl BitFont {
     ///
     /// Characters are spaced horizontally based on the scaled width of each character.
     /// Non-printable characters are skipped.
+    #[allow(clippy::too_many_arguments)]
     pub fn draw_text_smooth_scaled(&self, framebuffer: &mut [u32], fb_width: usize, text: &str, x: i32, y: i32, color: u32, scale: f32) {
         let char_width = (8.0 * scale) as i32;
         let mut current_x = x;
 
         // Iterate over each character in the text
-        for (_char_idx, ch) in text.chars().enumerate() {
+        for ch in text.chars() {
             let char_index = (ch as u8).saturating_sub(32) as usize;
             if char_index >= 95 { // Check if character is printable
                 continue;
@@ -1,39 +1,44 @@
-use crate::graphics::sprites::{draw_sprite, draw_sprite_with_gradient_shading};
+use crate::graphics::sprites::{draw_sprite, draw_sprite_scaled, draw_sprite_with_gradient_shading};
 use crate::graphics::text::{get_font_data, BitFont};
 use crate::graphics::sprites::SpriteMaps;
 use crate::state::constants::graphics::{ART_WIDTH, ART_HEIGHT};
+use crate::state::constants::placement::HUD_HEIGHT;
+use crate::state::constants::timing::{COMBO_WINDOW_TICKS, SPICY_TICKS};
+use crate::state::core::food::FoodKind;
+use crate::state::constants::text::{BOARD_CLEARED, CAMPAIGN_COMPLETE, PAUSED, PAUSED_HINT, STAGE_CLEAR};
+use crate::state::config::GameConfig;
 use crate::state::structs::{Direction, Snake, Food, LootCrate};
-use crate::state::core::perks::{get_default_powerups, Perk};
+use crate::state::core::combo::Combo;
+use crate::state::core::effects::ActiveEffect;
+use crate::state::level::{Obstacle, ObstacleKind};
+use crate::state::core::perks::{Perk, PerkEffect};
 
-fn get_powerup_sprite_indices() -> (usize, usize) {
-    let (powerup1, powerup2) = get_default_powerups();
-    (get_powerup_sprite_index(&powerup1), get_powerup_sprite_index(&powerup2))
-}
+// The top HUD row is split into fixed regions so its parts never run into each other: perk
+// icons on the left, the goal, score and combo in the middle, multipliers on the right
+const HUD_ICON_SLOTS: usize = 4; // 20px slots from x = 2
+const HUD_CENTER_START: i32 = 88;
+const HUD_CENTER_END: i32 = 168;
 
-fn get_powerup_sprite_index(powerup: &Perk) -> usize {
-    match powerup {
-        Perk::NeedForSpeed => 0,
-        Perk::HungryWorm => 1,
-        _ => 0
-    }
+/// Where the score and the combo label after it start, centered together in the middle region.
+fn score_row_layout(score: u32, combo: &Combo) -> (i32, i32) {
+    let score_width = score.to_string().len() as i32 * 8;
+    let combo_width = if combo.multiplier() >= 2 { 4 + combo_label(combo).len() as i32 * 8 } else { 0 };
+    let score_x = HUD_CENTER_START + (HUD_CENTER_END - HUD_CENTER_START - score_width - combo_width) / 2;
+    (score_x, score_x + score_width + 4)
 }
 
-fn get_powerup_info(powerup: &Perk) -> (&'static str, &'static str) {
-    match powerup {
-        Perk::NeedForSpeed => ("Need 4 Speed", "+25% movement speed"),
-        Perk::HungryWorm => ("Hungry Worm", "2x score from food"),
-        _ => ("Need 4 Speed", "+25% movement speed"),
-    }
+fn combo_label(combo: &Combo) -> String {
+    format!("x{}", combo.multiplier())
 }
 
-pub fn draw_score_text(art_buffer: &mut [u32], score: u32) {
+pub fn draw_score_text(art_buffer: &mut [u32], score: u32, combo: &Combo) {
     // Use the same font system as the original game
     let font_data = get_font_data();
     let bit_font = BitFont { chars: font_data };
     
-    // Draw just the score number
+    // Draw just the score number, leaving room for the combo after it
     let score_text = score.to_string();
-    let score_x = (ART_WIDTH as i32 / 2) - (score_text.len() as i32 * 4 / 2); // Center horizontally
+    let (score_x, _) = score_row_layout(score, combo);
     let score_y = 10; // Top of screen
     
     bit_font.draw_text_smooth_scaled(
@@ -47,24 +52,222 @@ pub fn draw_score_text(art_buffer: &mut [u32], score: u32) {
     );
 }
 
-pub fn draw_food(art_buffer: &mut [u32], food: &Food, sprites: &SpriteMaps) {
-    if food.is_active {
-        if !sprites.food.is_empty() {
-            // Use the appropriate sprite frame index, clamped to available sprites
-            let sprite_index = food.food_sprite_frame_index;
+/// The running combo, right of the score, with a bar showing how long is left to keep it going.
+pub fn draw_combo(art_buffer: &mut [u32], score: u32, combo: &Combo) {
+    if combo.multiplier() < 2 {
+        return;
+    }
+    let font_data = get_font_data();
+    let bit_font = BitFont { chars: font_data };
 
-            draw_sprite(
-                food.position.x as usize,
-                food.position.y as usize,
-                &sprites.food[sprite_index],
+    let (_, x) = score_row_layout(score, combo);
+    bit_font.draw_text_smooth_scaled(
+        art_buffer,
+        ART_WIDTH,
+        &combo_label(combo),
+        x,
+        10,
+        0xFFFF8C00, // Orange, stands out from the white score
+        1.0 // Normal scale
+    );
+    let filled = (16 * combo.remaining_ticks / COMBO_WINDOW_TICKS) as usize;
+    fill_rect(art_buffer, x as usize, 19, filled, 2, 0xFFFF8C00);
+}
+
+/// Draws the perk row of the HUD: a small icon for every active perk with a bar showing the time
+/// it has left (or a charge counter for Swap), the spicy food burst if one is running, and the
+/// current speed and score multipliers on the right.
+pub fn draw_perk_hud(
+    art_buffer: &mut [u32],
+    sprites: &SpriteMaps,
+    active_effects: &[ActiveEffect],
+    swap_charges: u32,
+    spicy_ticks: u64,
+    speed_multiplier: f32,
+    score_multiplier: f32,
+) {
+    let font_data = get_font_data();
+    let bit_font = BitFont { chars: font_data };
+
+    // Icons sit in 20px slots along the top-left corner, clear of the centered score. When they
+    // don't all fit, the last slot counts the rest instead
+    let icon_count = usize::from(spicy_ticks > 0) + active_effects.len() + usize::from(swap_charges > 0);
+    let shown = if icon_count > HUD_ICON_SLOTS { HUD_ICON_SLOTS - 1 } else { icon_count };
+    let mut slot_x = 2;
+
+    // Spicy food is not a perk; it shows its own sprite and burns down over its short burst
+    if spicy_ticks > 0 && shown > 0 {
+        if let Some(frame) = sprites.food_frames(FoodKind::Spicy).first() {
+            draw_sprite_scaled(slot_x, 1, (HUD_ICON_WIDTH, HUD_ICON_HEIGHT), frame, art_buffer, ART_WIDTH, None);
+        }
+        let filled = (HUD_ICON_WIDTH as u64 * spicy_ticks.min(SPICY_TICKS) / SPICY_TICKS) as usize;
+        fill_rect(art_buffer, slot_x, 17, HUD_ICON_WIDTH, 3, 0xFF404040); // Empty track
+        fill_rect(art_buffer, slot_x, 17, filled, 3, 0xFFFF4500); // Fiery time left
+        slot_x += 20;
+    }
+    let perk_slots = shown - usize::from(spicy_ticks > 0 && shown > 0);
+    for effect in active_effects.iter().take(perk_slots) {
+        let definition = effect.perk.definition();
+        draw_hud_icon(art_buffer, sprites, definition.sprite_index, slot_x);
+
+        // Timed perks get a depletion bar underneath; permanent ones don't run out
+        if let PerkEffect::Modifier { duration: Some(duration), .. } = definition.effect {
+            let remaining = effect.remaining_ticks.unwrap_or(0).min(duration);
+            let filled = (HUD_ICON_WIDTH as u64 * remaining / duration) as usize;
+            fill_rect(art_buffer, slot_x, 17, HUD_ICON_WIDTH, 3, 0xFF404040); // Empty track
+            fill_rect(art_buffer, slot_x, 17, filled, 3, 0xFFFFD700); // Golden time left
+        }
+        slot_x += 20;
+    }
+
+    // Swap is counted in charges rather than time
+    if swap_charges > 0 && active_effects.len() < perk_slots {
+        draw_hud_icon(art_buffer, sprites, Perk::Swap.definition().sprite_index, slot_x);
+        bit_font.draw_text_smooth_scaled(
+            art_buffer,
+            ART_WIDTH,
+            &swap_charges.to_string(),
+            slot_x as i32 + 4,
+            16,
+            0xFF58E4DA, // Teal, matches the Swap card
+            1.0 // Normal scale
+        );
+        slot_x += 20;
+    }
+    if icon_count > shown {
+        bit_font.draw_text_smooth_scaled(
+            art_buffer,
+            ART_WIDTH,
+            &format!("+{}", icon_count - shown),
+            slot_x as i32,
+            4,
+            0xFFCCCCCC, // Light grey, like the multipliers
+            1.0 // Normal scale
+        );
+    }
+
+    // Multipliers, right-aligned but never into the middle region
+    for (line, text) in [
+        format!("SPD x{:.2}", speed_multiplier),
+        format!("PTS x{:.2}", score_multiplier),
+    ]
+    .iter()
+    .enumerate()
+    {
+        bit_font.draw_text_smooth_scaled(
+            art_buffer,
+            ART_WIDTH,
+            text,
+            (ART_WIDTH as i32 - 2 - text.len() as i32 * 8).max(HUD_CENTER_END),
+            3 + line as i32 * 10,
+            0xFFCCCCCC, // Light grey, quieter than the score
+            1.0 // Normal scale
+        );
+    }
+}
+
+const HUD_ICON_WIDTH: usize = 16;
+const HUD_ICON_HEIGHT: usize = 14; // Keeps the 128x112 card proportions
+
+fn draw_hud_icon(art_buffer: &mut [u32], sprites: &SpriteMaps, sprite_index: usize, x: usize) {
+    if let Some(sprite) = sprites.powerups.get(sprite_index) {
+        draw_sprite_scaled(x, 1, (HUD_ICON_WIDTH, HUD_ICON_HEIGHT), sprite, art_buffer, ART_WIDTH, None);
+    }
+}
+
+fn fill_rect(art_buffer: &mut [u32], x: usize, y: usize, width: usize, height: usize, color: u32) {
+    for row in y..y + height {
+        for col in x..(x + width).min(ART_WIDTH) {
+            if let Some(pixel) = art_buffer.get_mut(row * ART_WIDTH + col) {
+                *pixel = color;
+            }
+        }
+    }
+}
+
+/// Outlines the walled playfield (everything below the HUD strip) in wall mode.
+pub fn draw_walls(art_buffer: &mut [u32]) {
+    let color = 0xFF40E0D0; // Turquoise, matches the page theme
+    let top = HUD_HEIGHT as usize - 2;
+    let height = ART_HEIGHT - top;
+
+    fill_rect(art_buffer, 0, top, ART_WIDTH, 2, color); // Top, just under the HUD
+    fill_rect(art_buffer, 0, ART_HEIGHT - 2, ART_WIDTH, 2, color); // Bottom
+    fill_rect(art_buffer, 0, top, 2, height, color); // Left
+    fill_rect(art_buffer, ART_WIDTH - 2, top, 2, height, color); // Right
+}
+
+pub fn draw_obstacles(art_buffer: &mut [u32], obstacles: &[Obstacle], sprites: &SpriteMaps) {
+    for obstacle in obstacles {
+        let position = obstacle.position();
+        let (sprite_index, fallback_color) = match obstacle.kind {
+            ObstacleKind::Asteroid => (0, 0xFF8C745C), // Brown rock
+            ObstacleKind::StationWall => (1, 0xFF828EA0), // Grey metal
+        };
+
+        match sprites.obstacles.get(sprite_index) {
+            Some(sprite) => draw_sprite(
+                position.x as usize,
+                position.y as usize,
+                sprite,
                 art_buffer,
                 ART_WIDTH,
                 None,
-            );
+            ),
+            // Plain tiles until the sprite sheet has loaded
+            None => fill_rect(art_buffer, position.x as usize, position.y as usize, 6, 8, fallback_color),
+        }
+    }
+}
+
+pub fn draw_food(art_buffer: &mut [u32], food: &[Food], config: &GameConfig, food_score_value: u32, sprites: &SpriteMaps) {
+    for food in food {
+        // Food about to expire blinks out every other period
+        if !crate::state::core::food::is_visible(food, config) {
+            continue;
+        }
+        draw_food_item(art_buffer, food, sprites);
+        if food.is_active && config.food_lifetime_ticks().is_some() {
+            draw_food_value(art_buffer, food, config, food_score_value);
         }
     }
 }
 
+/// What the food is still worth, in a label above it that shrinks as the value decays.
+fn draw_food_value(art_buffer: &mut [u32], food: &Food, config: &GameConfig, food_score_value: u32) {
+    let font_data = get_font_data();
+    let bit_font = BitFont { chars: font_data };
+    let label = crate::state::core::food::food_value(food, food_score_value, config).to_string();
+    let scale = 0.5 + 0.5 * crate::state::core::food::decay_factor(food, config);
+    let size = (8.0 * scale) as i32;
+    bit_font.draw_text_smooth_scaled(
+        art_buffer,
+        ART_WIDTH,
+        &label,
+        food.position.x as i32 + (16 - label.len() as i32 * size) / 2, // Centered over the 16px sprite
+        food.position.y as i32 - size - 1,
+        0xFFFFFFFF, // White color
+        scale
+    );
+}
+
+fn draw_food_item(art_buffer: &mut [u32], food: &Food, sprites: &SpriteMaps) {
+    let frames = sprites.food_frames(food.kind);
+    if food.is_active && !frames.is_empty() {
+        // Use the appropriate sprite frame index, clamped to available sprites
+        let sprite_index = food.food_sprite_frame_index.min(frames.len() - 1);
+
+        draw_sprite(
+            food.position.x as usize,
+            food.position.y as usize,
+            &frames[sprite_index],
+            art_buffer,
+            ART_WIDTH,
+            None,
+        );
+    }
+}
+
 pub fn draw_loot_crate(art_buffer: &mut [u32], loot_crate: &LootCrate, sprites: &SpriteMaps) {
     if loot_crate.is_active && !sprites.loot_crate.is_empty() {
         // Use the appropriate sprite frame index, clamped to available sprites
@@ -149,7 +352,7 @@ pub fn draw_snake(art_buffer: &mut [u32], player: &Snake, sprites: &SpriteMaps)
 pub fn draw_parallax_background(
     art_buffer: &mut [u32],
     sprites: &SpriteMaps,
-    stars_offset_x: usize,
+    stars_offset_x: f32, // Interpolated scroll position, see `Simulation::interpolation_alpha`
     stars_sprite_frame_index: usize,
     globe_sprite_frame_index: usize,
 ) {
@@ -158,8 +361,8 @@ pub fn draw_parallax_background(
     
     // Draw stars with parallax effect (layer 0)
     if !sprites.stars.is_empty() {
-        let parallax_divisor = 12; // Same as original
-        let offset_x = stars_offset_x / parallax_divisor;
+        let parallax_divisor = 12.0; // Same as original
+        let offset_x = (stars_offset_x / parallax_divisor) as usize;
         
         // Get the current star frame for blinking effect
         let star_frame_index = if sprites.stars.len() > 1 {
@@ -216,7 +419,7 @@ pub fn draw_parallax_background(
                     let shade_start = art_width_f / 1.9;
                     let shade_end = art_width_f / 1.7;
                     let progress = (x_f - shade_start) / (shade_end - shade_start);
-                    let progress = progress.min(1.0).max(0.0);
+                    let progress = progress.clamp(0.0, 1.0);
                     
                     // Interpolate between 0.8 (light shade) and 0.6 (dark shade)
                     let darkness = 0.8 - (progress * 0.2);
@@ -235,6 +438,7 @@ pub fn draw_game_over_screen(
     game_over_frame: usize,
     game_over_darkness: f32,
     score: u32,
+    best_combo: u32,
 ) {
     // Draw game over screen sprite with darkness factor
     if !sprites.game_over_screen.is_empty() && game_over_frame < sprites.game_over_screen.len() {
@@ -264,11 +468,196 @@ pub fn draw_game_over_screen(
         0xFFFFFFFF, // White color
         1.0 // Normal scale
     );
+
+    // The best combo of the run, just above the score
+    if best_combo >= 2 {
+        let combo_text = format!("Max combo: x{}", best_combo);
+        bit_font.draw_text_smooth_scaled(
+            art_buffer,
+            ART_WIDTH,
+            &combo_text,
+            (ART_WIDTH as i32 / 2) - (combo_text.len() as i32 * 4),
+            y_position - 12,
+            0xFFFF8C00, // Orange, like the combo in the HUD
+            1.0 // Normal scale
+        );
+    }
+}
+
+/// Best score on every difficulty preset, with the preset of the run that just ended in gold.
+pub fn draw_high_score_table(art_buffer: &mut [u32], high_scores: &[(&str, u32)], current: usize) {
+    let font_data = get_font_data();
+    let bit_font = BitFont { chars: font_data };
+
+    // Rows sit above the max combo and score lines
+    let top = ART_HEIGHT as i32 - 44 - high_scores.len() as i32 * 10;
+    for (row, (label, score)) in high_scores.iter().enumerate() {
+        let text = format!("{:<7}{:>7}", label, score);
+        bit_font.draw_text_smooth_scaled(
+            art_buffer,
+            ART_WIDTH,
+            &text,
+            (ART_WIDTH as i32 - text.len() as i32 * 8) / 2,
+            top + row as i32 * 10,
+            if row == current { 0xFFFFD700 } else { 0xFFCCCCCC }, // Gold for this run's preset
+            1.0 // Normal scale
+        );
+    }
+}
+
+pub fn draw_victory_screen(art_buffer: &mut [u32], sprites: &SpriteMaps, darkness: f32, score: u32) {
+    // Fade the planet backdrop out in step with the game over animation
+    if !sprites.planet.is_empty() {
+        draw_sprite(0, 0, &sprites.planet[0], art_buffer, ART_WIDTH, Some(darkness));
+    }
+
+    let font_data = get_font_data();
+    let bit_font = BitFont { chars: font_data };
+
+    let title_scale = 2.0;
+    let title_x = (ART_WIDTH as i32 - BOARD_CLEARED.len() as i32 * (8.0 * title_scale) as i32) / 2;
+    bit_font.draw_text_smooth_scaled(
+        art_buffer,
+        ART_WIDTH,
+        BOARD_CLEARED,
+        title_x,
+        ART_HEIGHT as i32 / 2 - 16,
+        0xFFFFD700, // Gold color
+        title_scale
+    );
+
+    // Draw the score near the bottom, like the game over screen
+    let score_text = format!("Score: {}", score);
+    let x_position = (ART_WIDTH as i32 - score_text.len() as i32 * 8) / 2;
+    bit_font.draw_text_smooth_scaled(
+        art_buffer,
+        ART_WIDTH,
+        &score_text,
+        x_position,
+        ART_HEIGHT as i32 - 20,
+        0xFFFFFFFF, // White color
+        1.0 // Normal scale
+    );
+}
+
+/// Shown when a campaign stage's goal is met, naming the stage that comes next.
+pub fn draw_stage_clear_screen(
+    art_buffer: &mut [u32],
+    sprites: &SpriteMaps,
+    darkness: f32,
+    stage: usize,
+    next_stage: Option<&str>,
+    score: u32,
+) {
+    if !sprites.planet.is_empty() {
+        draw_sprite(0, 0, &sprites.planet[0], art_buffer, ART_WIDTH, Some(darkness));
+    }
+
+    let font_data = get_font_data();
+    let bit_font = BitFont { chars: font_data };
+
+    let title_scale = 2.0;
+    let title_x = (ART_WIDTH as i32 - STAGE_CLEAR.len() as i32 * (8.0 * title_scale) as i32) / 2;
+    bit_font.draw_text_smooth_scaled(
+        art_buffer,
+        ART_WIDTH,
+        STAGE_CLEAR,
+        title_x,
+        ART_HEIGHT as i32 / 2 - 32,
+        0xFFFFD700, // Gold color
+        title_scale
+    );
+
+    let next_text = match next_stage {
+        Some(name) => format!("Stage {} - {}", stage + 2, name),
+        None => CAMPAIGN_COMPLETE.to_string(),
+    };
+    for (line, text) in [format!("Stage {} done", stage + 1), next_text].iter().enumerate() {
+        bit_font.draw_text_smooth_scaled(
+            art_buffer,
+            ART_WIDTH,
+            text,
+            (ART_WIDTH as i32 - text.len() as i32 * 8) / 2,
+            ART_HEIGHT as i32 / 2 + line as i32 * 12,
+            0xFF40E0D0, // Turquoise, matches the page theme
+            1.0 // Normal scale
+        );
+    }
+
+    let score_text = format!("Score: {}", score);
+    bit_font.draw_text_smooth_scaled(
+        art_buffer,
+        ART_WIDTH,
+        &score_text,
+        (ART_WIDTH as i32 - score_text.len() as i32 * 8) / 2,
+        ART_HEIGHT as i32 - 20,
+        0xFFFFFFFF, // White color
+        1.0 // Normal scale
+    );
+}
+
+/// Progress towards the level's goal, centered above the score and shrunk to fit the middle of
+/// the HUD.
+pub fn draw_goal_progress(art_buffer: &mut [u32], label: &str) {
+    let font_data = get_font_data();
+    let bit_font = BitFont { chars: font_data };
+    let region = HUD_CENTER_END - HUD_CENTER_START;
+    let scale = (region as f32 / (label.len() as f32 * 8.0)).min(1.0);
+    let width = label.len() as i32 * (8.0 * scale) as i32;
+    bit_font.draw_text_smooth_scaled(
+        art_buffer,
+        ART_WIDTH,
+        label,
+        HUD_CENTER_START + (region - width) / 2,
+        1,
+        0xFFFFD700, // Gold, like the other goal texts
+        scale
+    );
+}
+
+pub fn draw_pause_overlay(art_buffer: &mut [u32]) {
+    // Dim the frozen game scene to 40% brightness
+    for pixel in art_buffer.iter_mut() {
+        let r = ((*pixel >> 16) & 0xFF) * 2 / 5;
+        let g = ((*pixel >> 8) & 0xFF) * 2 / 5;
+        let b = (*pixel & 0xFF) * 2 / 5;
+        *pixel = 0xFF000000 | (r << 16) | (g << 8) | b;
+    }
+
+    let font_data = get_font_data();
+    let bit_font = BitFont { chars: font_data };
+
+    // Large centered "PAUSED" title
+    let title_scale = 2.0;
+    let title_x = (ART_WIDTH as i32 - PAUSED.len() as i32 * (8.0 * title_scale) as i32) / 2;
+    let title_y = ART_HEIGHT as i32 / 2 - 16;
+    bit_font.draw_text_smooth_scaled(
+        art_buffer,
+        ART_WIDTH,
+        PAUSED,
+        title_x,
+        title_y,
+        0xFFFFFFFF, // White color
+        title_scale
+    );
+
+    // Resume hint underneath
+    let hint_x = (ART_WIDTH as i32 - PAUSED_HINT.len() as i32 * 8) / 2;
+    bit_font.draw_text_smooth_scaled(
+        art_buffer,
+        ART_WIDTH,
+        PAUSED_HINT,
+        hint_x,
+        title_y + 24,
+        0xFF40E0D0, // Turquoise, matches the page theme
+        1.0 // Normal scale
+    );
 }
 
 pub fn draw_powerup_selection_screen(
     art_buffer: &mut [u32],
     sprites: &SpriteMaps,
+    powerup_offers: &[Perk],
     highlighted_powerup: Option<usize>,
 ) {
     // Draw the top part of the powerup screen (choose powerup prompt)
@@ -296,29 +685,25 @@ pub fn draw_powerup_selection_screen(
         1.7 // Scale
     );
     
-    // Get the default powerup sprite indices
-    let powerup_sprite_indices = get_powerup_sprite_indices();
-    
-    // Draw the two powerup options
-    let powerup_positions = [(0, ART_HEIGHT / 2), (128, ART_HEIGHT / 2)];
-    let sprite_indices = [powerup_sprite_indices.0, powerup_sprite_indices.1];
-    
-    for (i, &(x, y)) in powerup_positions.iter().enumerate() {
+    // Draw the offered cards side by side on the bottom half
+    for (i, perk) in powerup_offers.iter().enumerate() {
         let powerup_index = i + 1;
         let is_highlighted = highlighted_powerup == Some(powerup_index);
-        let sprite_index = sprite_indices[i];
+        let sprite_index = perk.definition().sprite_index;
         
-        if sprite_index < sprites.powerups.len() {
+        if let Some(sprite) = sprites.powerups.get(sprite_index) {
             let darkness_factor = if is_highlighted {
                 Some(0.8) // Highlighted - slightly dim
             } else {
                 Some(0.5) // Normal - more dim
             };
             
-            draw_sprite(
+            let (x, y, width, height) = powerup_card_bounds(powerup_index, powerup_offers.len());
+            draw_sprite_scaled(
                 x,
                 y,
-                &sprites.powerups[sprite_index],
+                (width, height),
+                sprite,
                 art_buffer,
                 ART_WIDTH,
                 darkness_factor,
@@ -327,20 +712,17 @@ pub fn draw_powerup_selection_screen(
     }
     
     // Draw information about the highlighted powerup
-    if let Some(powerup_index) = highlighted_powerup {
-        let (powerup1, powerup2) = get_default_powerups();
-        let selected_powerup = match powerup_index {
-            1 => &powerup1,
-            2 => &powerup2,
-            _ => &powerup1, // Default fallback
-        };
-        let (powerup_title, powerup_description) = get_powerup_info(selected_powerup);
+    let selected_powerup = highlighted_powerup
+        .and_then(|powerup_index| powerup_offers.get(powerup_index.saturating_sub(1)))
+        .or(powerup_offers.first()); // Default fallback
+    if let Some(selected_powerup) = selected_powerup {
+        let definition = selected_powerup.definition();
         
         // Draw the first line of powerup information
         bit_font.draw_text_smooth_scaled(
             art_buffer,
             ART_WIDTH,
-            powerup_title,
+            definition.name,
             75, // X position
             55, // Y position
             0xFFFFD700, // Golden color with full alpha
@@ -351,11 +733,36 @@ pub fn draw_powerup_selection_screen(
         bit_font.draw_text_smooth_scaled(
             art_buffer,
             ART_WIDTH,
-            powerup_description,
+            definition.description,
             52, // X position
             69, // Y position
             0xCCCCCCFF, // Slightly grey color with full alpha
             1.0 // Scale
         );
     }
-}
\ No newline at end of file
+}
+
+const POWERUP_CARD_WIDTH: usize = 128;
+const POWERUP_CARD_HEIGHT: usize = 112;
+
+/// Where card `powerup_index` (numbered from 1) of `card_count` sits on the selection screen, as
+/// (x, y, width, height). Cards share the bottom half equally and shrink when more than two are
+/// offered, so one to four fit side by side.
+pub fn powerup_card_bounds(powerup_index: usize, card_count: usize) -> (usize, usize, usize, usize) {
+    let slot_width = ART_WIDTH / card_count.max(1);
+    let scale = (slot_width as f32 / POWERUP_CARD_WIDTH as f32).min(1.0);
+    let width = (POWERUP_CARD_WIDTH as f32 * scale) as usize;
+    let height = (POWERUP_CARD_HEIGHT as f32 * scale) as usize;
+
+    let x = powerup_index.saturating_sub(1) * slot_width + (slot_width - width) / 2;
+    let y = ART_HEIGHT / 2 + (ART_HEIGHT / 2 - height) / 2;
+    (x, y, width, height)
+}
+
+/// The card (numbered from 1) under the art-space point (`x`, `y`), if any.
+pub fn powerup_card_at(x: f32, y: f32, card_count: usize) -> Option<usize> {
+    (1..=card_count).find(|&powerup_index| {
+        let (card_x, card_y, width, height) = powerup_card_bounds(powerup_index, card_count);
+        x >= card_x as f32 && x < (card_x + width) as f32 && y >= card_y as f32 && y < (card_y + height) as f32
+    })
+}
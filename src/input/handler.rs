@@ -28,11 +28,11 @@ pub fn handle_key_down(
 
     // Only change direction if it's not opposite to current direction
     if let Some(direction) = new_direction {
-        let can_change = match (*player_direction, direction) {
+        let can_change = !matches!(
+            (*player_direction, direction),
             (Direction::Up, Direction::Down) | (Direction::Down, Direction::Up) |
-            (Direction::Left, Direction::Right) | (Direction::Right, Direction::Left) => false,
-            _ => true,
-        };
+            (Direction::Left, Direction::Right) | (Direction::Right, Direction::Left)
+        );
 
         if can_change {
             *player_direction = direction;
@@ -43,4 +43,14 @@ pub fn handle_key_down(
 pub fn handle_game_over_input(key_code: &str) -> bool {
     // Allow restarting the game with Space key
     key_code == "Space"
-}
\ No newline at end of file
+}
+
+pub fn handle_swap_input(key_code: &str) -> bool {
+    // Spend a Swap charge with E
+    key_code == "KeyE"
+}
+
+pub fn handle_pause_input(key_code: &str) -> bool {
+    // Toggle pause with Escape during normal play
+    key_code == "Escape"
+}
@@ -1,15 +1,28 @@
+use std::collections::HashMap;
+
 use image::GenericImageView;
 use wasm_bindgen::prelude::*;
 
-mod state;
-mod graphics;
-mod input;
-mod audio;
-mod platform;
+pub mod state;
+pub mod graphics;
+pub mod input;
+pub mod audio;
+pub mod platform;
 
 use crate::graphics::sprites::SpriteMaps;
+use crate::platform::{WebClock, WebInput, WebStorage};
 use crate::state::constants::graphics::{ART_HEIGHT, ART_WIDTH, SCALED_WINDOW_HEIGHT, SCALED_WINDOW_WIDTH};
-use crate::state::structs::{Direction, Snake};
+use crate::state::constants::timing::STARS_SCROLL_TICKS;
+use crate::state::config::GameConfig;
+use crate::state::core::difficulty::{Difficulty, DIFFICULTIES};
+use crate::state::core::food::FoodKind;
+use crate::state::replay::Replay;
+use crate::state::simulation::{Simulation, SimulationEvent};
+
+// localStorage key for the highest campaign stage unlocked
+const CAMPAIGN_PROGRESS_KEY: &str = "space_worm_campaign";
+// localStorage key for the best score on each difficulty, stored as "easy:1200,normal:800"
+const HIGH_SCORES_KEY: &str = "space_worm_high_scores";
 
 
 // Set up console error panic hook for better debugging
@@ -142,42 +155,26 @@ pub struct WasmGame {
     canvas: web_sys::HtmlCanvasElement,
     context: web_sys::CanvasRenderingContext2d,
     pixel_buffer: Vec<u32>,
-    player: Snake,
-    food: state::structs::Food,
-    loot_crate: state::structs::LootCrate,
     sprites: SpriteMaps,
-    score: u32,
-    game_over: bool,
-    last_frame_time: Option<f64>,
-    // Game over animation variables
-    game_over_frame: usize,
-    game_over_darkness: f32,
-    game_over_animation_time: f64,
-    // Background parallax and animation variables
-    stars_offset_x: usize,
-    stars_sprite_frame_index: usize,
-    stars_last_sprite_frame_update_time: f64,
-    globe_sprite_frame_index: usize,
-    globe_last_sprite_frame_update_time: f64,
-    // Powerup system variables
-    powerup_eligibility: bool,
-    selected_powerup: Option<crate::state::core::perks::Perk>,
-    last_loot_spawn_score: u32,
-    food_score_value: u32,
-    in_powerup_selection: bool,
-    highlighted_powerup: Option<usize>,
-    powerup_selection_keys: std::collections::HashMap<String, bool>,
-    powerup_sound_played: bool,
-    // Loot crate timer
-    last_loot_crate_check_time: f64,
-    // Game over crash sound state
-    crash_sound_played: bool,
+    input: WebInput,
+    sim: Simulation<WebClock>,
 }
 
 #[wasm_bindgen]
 impl WasmGame {
+    /// Creates a game with the given rules, or the default ones when `config` is omitted.
     #[wasm_bindgen(constructor)]
-    pub fn new() -> Result<WasmGame, JsValue> {
+    pub fn new(config: Option<GameConfig>) -> Result<WasmGame, JsValue> {
+        // Pick a fresh seed for casual play, and a new one for every run after it
+        let seed = (js_sys::Math::random() * u32::MAX as f64) as u64;
+        let mut game = WasmGame::new_with_seed(seed, config)?;
+        game.sim.set_fresh_seed_each_run(true);
+        Ok(game)
+    }
+
+    /// Creates a game whose food and loot crate rolls are fully determined by `seed`.
+    #[wasm_bindgen]
+    pub fn new_with_seed(seed: u64, config: Option<GameConfig>) -> Result<WasmGame, JsValue> {
         let document = web_sys::window()
             .ok_or("No global window object")?
             .document()
@@ -196,23 +193,18 @@ impl WasmGame {
             .ok_or("Failed to get 2d context")?
             .dyn_into::<web_sys::CanvasRenderingContext2d>()?;
 
+        // Track clicks and taps on the canvas
+        let input = WebInput::new(&canvas)?;
+
         // Create pixel buffer for scaled resolution
-        let buffer_size = (SCALED_WINDOW_WIDTH * SCALED_WINDOW_HEIGHT) as usize;
+        let buffer_size = SCALED_WINDOW_WIDTH * SCALED_WINDOW_HEIGHT;
         let pixel_buffer = vec![0xFF000000u32; buffer_size]; // Black background with full alpha
 
-        // Initialize game state
-        let player = Snake::new(40.0, 150.0, Direction::Right);
-        let food = crate::state::structs::Food {
-            position: crate::state::structs::Vector2D { x: 200.0, y: 200.0 },
-            is_active: true,
-            food_sprite_frame_index: 0,
-            food_last_sprite_frame_index_update_time: 0.0,
-        };
-
         // Start with empty sprites, actual loading happens separately
         let sprites = SpriteMaps {
             body: vec![],
             food: vec![],
+            food_kinds: HashMap::new(),
             head: vec![],
             tail: vec![],
             game_over_screen: vec![],
@@ -222,47 +214,30 @@ impl WasmGame {
             powerups: vec![],
             choose_powerup: vec![],
             loot_crate: vec![],
+            obstacles: vec![],
         };
 
+        // Campaign progress carries over from earlier visits
+        let mut sim = Simulation::with_config(WebClock, seed, config.unwrap_or_default());
+        if let Some(unlocked) = WebStorage::get(CAMPAIGN_PROGRESS_KEY).and_then(|value| value.parse().ok()) {
+            sim.set_campaign_unlocked(unlocked);
+        }
+        for entry in WebStorage::get(HIGH_SCORES_KEY).unwrap_or_default().split(',') {
+            let Some((id, score)) = entry.split_once(':') else {
+                continue;
+            };
+            if let (Some(difficulty), Ok(score)) = (Difficulty::from_id(id), score.parse()) {
+                sim.set_high_score(difficulty, score);
+            }
+        }
+
         Ok(WasmGame {
             canvas,
             context,
             pixel_buffer,
-            player,
-            food,
-            loot_crate: state::structs::LootCrate {
-                position: state::structs::Vector2D { x: 0.0, y: 0.0 },
-                is_active: false,
-                sprite_frame_index: 0,
-                last_sprite_frame_index_update_time: 0.0,
-            },
             sprites,
-            score: 0,
-            game_over: false,
-            last_frame_time: None,
-            // Initialize game over animation
-            game_over_frame: 0,
-            game_over_darkness: 0.5,
-            game_over_animation_time: 0.0,
-            // Initialize background animation variables
-            stars_offset_x: 0,
-            stars_sprite_frame_index: 0,
-            stars_last_sprite_frame_update_time: 0.0,
-            globe_sprite_frame_index: 0,
-            globe_last_sprite_frame_update_time: 0.0,
-            // Initialize powerup system variables
-            powerup_eligibility: false,
-            selected_powerup: None,
-            last_loot_spawn_score: 0,
-            food_score_value: 100,
-            in_powerup_selection: false,
-            highlighted_powerup: None,
-            powerup_selection_keys: std::collections::HashMap::new(),
-            powerup_sound_played: false,
-            // Initialize loot crate timer
-            last_loot_crate_check_time: 0.0,
-            // Initialize crash sound state
-            crash_sound_played: false,
+            input,
+            sim,
         })
     }
 
@@ -283,6 +258,13 @@ impl WasmGame {
         Ok(())
     }
 
+    /// Adds an animation frame for a special food kind (`golden`, `rotten`, `spicy` or `cosmic`).
+    #[wasm_bindgen]
+    pub fn add_food_kind_sprite(&mut self, kind: &str, width: u32, height: u32, data: Vec<u32>) -> Result<(), JsValue> {
+        let kind = FoodKind::from_id(kind).ok_or_else(|| JsValue::from_str(&format!("Unknown food kind {}", kind)))?;
+        graphics::sprites::add_food_kind_sprite(&mut self.sprites, kind, width, height, data)
+    }
+
     #[wasm_bindgen]
     pub fn add_tail_sprite(&mut self, width: u32, height: u32, data: Vec<u32>) -> Result<(), JsValue> {
         graphics::sprites::add_tail_sprite(&mut self.sprites, width, height, data)
@@ -320,107 +302,195 @@ impl WasmGame {
         Ok(())
     }
 
-
     #[wasm_bindgen]
-    pub fn get_canvas(&self) -> web_sys::HtmlCanvasElement {
-        self.canvas.clone()
+    pub fn add_obstacle_sprite(&mut self, width: u32, height: u32, data: Vec<u32>) -> Result<(), JsValue> {
+        graphics::sprites::add_obstacle_sprite(&mut self.sprites, width, height, data)
     }
 
     #[wasm_bindgen]
-    pub fn tick(&mut self) -> Result<(), JsValue> {
-        if self.game_over {
-            // Play crash sound once when game over starts
-            if !self.crash_sound_played {
-                self.play_crash_sound();
-                self.crash_sound_played = true;
-                web_sys::console::log_1(&"Game over - playing crash sound".into());
-            }
-            
-            // Handle game over animation (will be paused until sound finishes)
-            self.update_game_over_animation();
-            self.render()?;
-            return Ok(());
-        }
+    pub fn get_seed(&self) -> u64 {
+        self.sim.state.seed
+    }
 
-        // Handle powerup selection
-        if self.in_powerup_selection {
-            self.handle_powerup_selection();
-            self.render()?;
-            return Ok(());
-        }
+    /// Switches between free movement and grid mode (discrete cells). Starts a new run.
+    #[wasm_bindgen]
+    pub fn set_grid_mode(&mut self, enabled: bool) {
+        self.sim.set_config(GameConfig { grid_mode: enabled, ..self.sim.state.config });
+        self.handle_simulation_events();
+    }
 
-        // Calculate delta time
-        let current_time = js_sys::Date::now();
-        let delta_time = if let Some(last_time) = self.last_frame_time {
-            (current_time - last_time) / 1000.0 // Convert to seconds
-        } else {
-            0.016 // ~60 FPS fallback
+    /// Starts a new run on built-in level `index` (0 is open space). Returns `false` if there is no
+    /// such level.
+    #[wasm_bindgen]
+    pub fn select_level(&mut self, index: usize) -> bool {
+        let Some(level) = state::level::builtin_level(index) else {
+            return false;
         };
-        self.last_frame_time = Some(current_time);
+        self.sim.load_level(level);
+        self.handle_simulation_events();
+        true
+    }
+
+    /// Starts a new run on a level written in the level text format (see `Level::parse`). Fails
+    /// with the line and column of the first problem in the text.
+    #[wasm_bindgen]
+    pub fn load_level(&mut self, text: &str) -> Result<(), JsValue> {
+        let level = state::level::Level::parse(text)
+            .map_err(|e| JsValue::from_str(&format!("Failed to load level: {}", e)))?;
+        self.sim.load_level(level);
+        self.handle_simulation_events();
+        Ok(())
+    }
+
+    /// Starts campaign stage `stage` (0 is the first). Returns `false` if it is still locked or
+    /// does not exist.
+    #[wasm_bindgen]
+    pub fn start_campaign(&mut self, stage: usize) -> bool {
+        let started = self.sim.start_campaign(stage);
+        self.handle_simulation_events();
+        started
+    }
 
-        // Update game logic
-        self.update_game_logic(delta_time as f32)?;
+    /// Highest campaign stage the player has unlocked, counted from 0.
+    #[wasm_bindgen]
+    pub fn campaign_unlocked(&self) -> usize {
+        self.sim.campaign_unlocked()
+    }
+
+    /// Best score of any run on `difficulty`, kept across visits.
+    #[wasm_bindgen]
+    pub fn high_score(&self, difficulty: Difficulty) -> u32 {
+        self.sim.high_score(difficulty)
+    }
 
-        // Render the game
-        self.render()?;
+    /// Binary replay of the last finished run (or the current one) for bug reports and score checks.
+    #[wasm_bindgen]
+    pub fn export_replay(&self) -> Vec<u8> {
+        self.sim.export_replay().to_bytes()
+    }
 
+    /// Restarts the game and plays back a replay produced by `export_replay`.
+    #[wasm_bindgen]
+    pub fn load_replay(&mut self, data: &[u8]) -> Result<(), JsValue> {
+        let replay = Replay::from_bytes(data)
+            .map_err(|e| JsValue::from_str(&format!("Failed to load replay: {}", e)))?;
+        self.sim.load_replay(replay);
+        self.handle_simulation_events();
         Ok(())
     }
 
-    fn update_game_logic(&mut self, delta_time: f32) -> Result<(), JsValue> {
-        // Store previous values to detect state changes
-        let previous_score = self.score;
-        let previous_in_powerup_selection = self.in_powerup_selection;
-        let _previous_game_over = self.game_over;
-        
-        let game_over = crate::state::core::tick::update_game_logic(
-            &mut self.player,
-            &mut self.food,
-            &mut self.loot_crate,
-            &mut self.score,
-            self.food_score_value,
-            &mut self.powerup_eligibility,
-            &mut self.in_powerup_selection,
-            &mut self.highlighted_powerup,
-            &mut self.stars_offset_x,
-            &mut self.stars_sprite_frame_index,
-            &mut self.stars_last_sprite_frame_update_time,
-            &mut self.globe_sprite_frame_index,
-            &mut self.globe_last_sprite_frame_update_time,
-            &mut self.last_loot_crate_check_time,
-            delta_time,
-        )?;
-
-        // Check if food was eaten (score increased)
-        if self.score > previous_score {
-            self.play_eat_sound();
-        }
+    /// Serializes the run in progress so it can be resumed later with `load_state`.
+    #[wasm_bindgen]
+    pub fn save_state(&self) -> Vec<u8> {
+        self.sim.save_state()
+    }
 
+    #[wasm_bindgen]
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), JsValue> {
+        self.sim
+            .load_state(data)
+            .map_err(|e| JsValue::from_str(&format!("Failed to load saved game: {}", e)))?;
+        self.handle_simulation_events();
+        Ok(())
+    }
 
+    #[wasm_bindgen]
+    pub fn get_canvas(&self) -> web_sys::HtmlCanvasElement {
+        self.canvas.clone()
+    }
 
-        // Check if powerup selection just started - pause music and play powerup sound
-        if !previous_in_powerup_selection && self.in_powerup_selection {
-            web_sys::console::log_1(&"Powerup selection started, pausing music".into());
-            self.pause_music();
-            self.play_new_powerup_sound();
-        }
+    #[wasm_bindgen]
+    pub fn tick(&mut self) -> Result<(), JsValue> {
+        self.handle_clicks();
+        self.sim.step();
+        self.handle_simulation_events();
+        self.render()
+    }
 
-        // Check if powerup selection just ended - resume music
-        if previous_in_powerup_selection && !self.in_powerup_selection {
-            web_sys::console::log_1(&"Powerup selection ended, resuming music".into());
-            self.resume_music();
-        }
+    fn handle_clicks(&mut self) {
+        for (x, y) in self.input.take_clicks() {
+            if !self.sim.state.in_powerup_selection || self.sim.is_paused() {
+                continue; // Only the powerup cards react to clicks
+            }
 
-        // Check if game just ended
-        if game_over {
-            self.game_over = true;
-            // Stop music immediately when game over occurs
-            self.stop_music();
+            // Canvas pixels to art pixels
+            let art_x = x * ART_WIDTH as f32 / SCALED_WINDOW_WIDTH as f32;
+            let art_y = y * ART_HEIGHT as f32 / SCALED_WINDOW_HEIGHT as f32;
+            let card_count = self.sim.state.powerup_offers.len();
+            if let Some(card) = graphics::update::powerup_card_at(art_x, art_y, card_count) {
+                // Picking a card by click goes through the same path as its number key,
+                // so replays record it like any other input
+                self.handle_key_down(&format!("Digit{}", card));
+            }
         }
-
-        Ok(())
     }
 
+    fn handle_simulation_events(&mut self) {
+        for event in self.sim.drain_events() {
+            match event {
+                SimulationEvent::FoodEaten(FoodKind::Cosmic) => {
+                    // Cosmic food hands out a perk, so it gets the perk chime
+                    self.play_new_powerup_sound();
+                }
+                SimulationEvent::FoodEaten(_) => self.play_eat_sound(),
+                SimulationEvent::PowerupSelectionStarted => {
+                    web_sys::console::log_1(&"Powerup selection started, pausing music".into());
+                    self.pause_music();
+                    self.play_new_powerup_sound();
+                }
+                SimulationEvent::PowerupSelectionEnded(Some(perk)) => {
+                    let definition = perk.definition();
+                    web_sys::console::log_1(&format!("{} selected, playing {} sound", definition.name, definition.sound).into());
+                    self.play_powerup_sound(definition.sound); // This will resume music when sound ends
+                }
+                SimulationEvent::PowerupSelectionEnded(None) => {
+                    web_sys::console::log_1(&"No powerup selected, resuming music".into());
+                    self.resume_music();
+                }
+                SimulationEvent::SwapTriggered => self.play_swap_sound(),
+                SimulationEvent::GameOver => {
+                    // Stop music immediately and play the crash sound once
+                    self.stop_music();
+                    self.play_crash_sound();
+                    web_sys::console::log_1(&"Game over - playing crash sound".into());
+                }
+                SimulationEvent::Won => {
+                    // The worm filled the board
+                    self.stop_music();
+                    self.play_win_sound();
+                    web_sys::console::log_1(&"Board cleared - playing win sound".into());
+                }
+                SimulationEvent::StageCleared(stage) => {
+                    self.stop_music();
+                    self.play_win_sound();
+                    web_sys::console::log_1(&format!("Campaign stage {} cleared", stage + 1).into());
+                    WebStorage::set(CAMPAIGN_PROGRESS_KEY, &self.sim.campaign_unlocked().to_string());
+                }
+                SimulationEvent::NewHighScore(difficulty) => {
+                    web_sys::console::log_1(&format!("New {} high score", difficulty.definition().id).into());
+                    let table: Vec<String> = DIFFICULTIES
+                        .iter()
+                        .map(|definition| format!("{}:{}", definition.id, self.sim.high_score(definition.difficulty)))
+                        .collect();
+                    WebStorage::set(HIGH_SCORES_KEY, &table.join(","));
+                }
+                SimulationEvent::Restarted => {
+                    // Restart background music
+                    self.stop_music();
+                    self.resume_music();
+                }
+                SimulationEvent::Paused => self.pause_music(),
+                SimulationEvent::Resumed => self.resume_music(),
+                SimulationEvent::StateLoaded => {
+                    // Only play music if the resumed run is in normal play
+                    self.stop_music();
+                    if !self.sim.state.game_over && !self.sim.state.in_powerup_selection {
+                        self.resume_music();
+                    }
+                }
+            }
+        }
+    }
 
     fn render(&mut self) -> Result<(), JsValue> {
         // Clear pixel buffer
@@ -428,45 +498,102 @@ impl WasmGame {
 
         // Create a temporary art-resolution buffer for rendering
         let mut art_buffer = vec![0xFF000000u32; ART_WIDTH * ART_HEIGHT];
+        let state = &self.sim.state;
 
-        if self.game_over {
+        if let (true, Some(stage)) = (state.game_over && state.won, state.campaign_stage) {
+            // Draw the stage clear screen, announcing the next stage
+            let next_stage = state::campaign::stage_name(stage + 1);
+            graphics::update::draw_stage_clear_screen(
+                &mut art_buffer,
+                &self.sprites,
+                state.game_over_animation.darkness,
+                stage,
+                next_stage,
+                state.score,
+            );
+        } else if state.game_over && state.won {
+            // Draw victory screen for a full board
+            graphics::update::draw_victory_screen(
+                &mut art_buffer,
+                &self.sprites,
+                state.game_over_animation.darkness,
+                state.score,
+            );
+        } else if state.game_over {
             // Draw game over screen
             graphics::update::draw_game_over_screen(
                 &mut art_buffer,
                 &self.sprites,
-                self.game_over_frame,
-                self.game_over_darkness,
-                self.score,
+                state.game_over_animation.frame,
+                state.game_over_animation.darkness,
+                state.score,
+                state.combo.best,
             );
-        } else if self.in_powerup_selection {
+            let high_scores: Vec<(&str, u32)> = DIFFICULTIES
+                .iter()
+                .map(|definition| (definition.label, self.sim.high_score(definition.difficulty)))
+                .collect();
+            graphics::update::draw_high_score_table(&mut art_buffer, &high_scores, state.config.difficulty.index());
+        } else if state.in_powerup_selection {
             // Draw powerup selection screen
             graphics::update::draw_powerup_selection_screen(
                 &mut art_buffer,
                 &self.sprites,
-                self.highlighted_powerup,
+                &state.powerup_offers,
+                state.highlighted_powerup,
             );
         } else {
+            // Interpolate the parallax scroll between logic ticks
+            let stars_offset_x = state.background.stars_offset_x as f32 + self.sim.interpolation_alpha() / STARS_SCROLL_TICKS as f32;
+
             // Draw background with parallax effect
             graphics::update::draw_parallax_background(
                 &mut art_buffer,
                 &self.sprites,
-                self.stars_offset_x,
-                self.stars_sprite_frame_index,
-                self.globe_sprite_frame_index,
+                stars_offset_x,
+                state.background.stars_sprite_frame_index,
+                state.background.globe_sprite_frame_index,
             );
 
+            // Asteroids and station walls sit on top of the background
+            graphics::update::draw_obstacles(&mut art_buffer, &state.level.obstacles, &self.sprites);
+
+            // Outline the playfield when its edges are deadly
+            if state.walls() {
+                graphics::update::draw_walls(&mut art_buffer);
+            }
+
             // Draw food
-            graphics::update::draw_food(&mut art_buffer, &self.food, &self.sprites);
+            graphics::update::draw_food(&mut art_buffer, &state.food, &state.config, state.food_score_value, &self.sprites);
 
             // Draw loot crate if active
-            graphics::update::draw_loot_crate(&mut art_buffer, &self.loot_crate, &self.sprites);
+            graphics::update::draw_loot_crate(&mut art_buffer, &state.loot_crate, &self.sprites);
 
 
             // Draw snake
-            graphics::update::draw_snake(&mut art_buffer, &self.player, &self.sprites);
+            graphics::update::draw_snake(&mut art_buffer, &state.player, &self.sprites);
 
             // Draw score text BEFORE scaling (only in normal game mode)
-            graphics::update::draw_score_text(&mut art_buffer, self.score);
+            graphics::update::draw_score_text(&mut art_buffer, state.score, &state.combo);
+            graphics::update::draw_combo(&mut art_buffer, state.score, &state.combo);
+            if let Some(goal) = state.level.goal {
+                let label = goal.label(state.score, state.player.body.len(), state.play_ticks);
+                graphics::update::draw_goal_progress(&mut art_buffer, &label);
+            }
+            graphics::update::draw_perk_hud(
+                &mut art_buffer,
+                &self.sprites,
+                &state.active_effects,
+                state.swap_charges,
+                state.spicy_ticks,
+                state.speed_multiplier(),
+                state.score_multiplier(),
+            );
+
+            // Dim the frozen scene while paused
+            if self.sim.is_paused() {
+                graphics::update::draw_pause_overlay(&mut art_buffer);
+            }
         }
 
         // Scale the art buffer to the screen buffer
@@ -480,145 +607,80 @@ impl WasmGame {
 
     #[wasm_bindgen]
     pub fn handle_key_down(&mut self, key_code: &str) {
-        if self.game_over {
-            // Allow restarting the game with Space key
-            if input::handler::handle_game_over_input(key_code) {
-                self.restart_game();
-            }
-            return;
-        }
+        self.sim.handle_key_down(key_code);
+        self.handle_simulation_events();
+    }
+
+    /// Pauses normal play, e.g. when the page is hidden. Ignored on other screens.
+    #[wasm_bindgen]
+    pub fn pause(&mut self) {
+        self.sim.pause();
+        self.handle_simulation_events();
+    }
 
-        input::handler::handle_key_down(
-            key_code,
-            &mut self.player.direction,
-            self.game_over,
-            self.in_powerup_selection,
-            &mut self.powerup_selection_keys,
-        );
+    #[wasm_bindgen]
+    pub fn resume(&mut self) {
+        self.sim.resume();
+        self.handle_simulation_events();
+    }
+
+    #[wasm_bindgen]
+    pub fn is_paused(&self) -> bool {
+        self.sim.is_paused()
     }
 
     #[wasm_bindgen]
     pub fn play_eat_sound(&self) {
         let js_code = "if (window.playSound) { window.playSound('eat'); }";
-        js_sys::eval(js_code).unwrap_or_else(|_| wasm_bindgen::JsValue::UNDEFINED);
+        js_sys::eval(js_code).unwrap_or(wasm_bindgen::JsValue::UNDEFINED);
     }
 
     #[wasm_bindgen]
     pub fn play_new_powerup_sound(&self) {
         let js_code = "if (window.playSound) { window.playSound('divine_intervention'); }";
-        js_sys::eval(js_code).unwrap_or_else(|_| wasm_bindgen::JsValue::UNDEFINED);
+        js_sys::eval(js_code).unwrap_or(wasm_bindgen::JsValue::UNDEFINED);
     }
 
     #[wasm_bindgen]
     pub fn play_powerup_sound(&self, sound_name: &str) {
         let js_code = format!("if (window.playPowerupSound) {{ window.playPowerupSound('{}'); }}", sound_name);
-        js_sys::eval(&js_code).unwrap_or_else(|_| wasm_bindgen::JsValue::UNDEFINED);
+        js_sys::eval(&js_code).unwrap_or(wasm_bindgen::JsValue::UNDEFINED);
+    }
+
+    #[wasm_bindgen]
+    pub fn play_swap_sound(&self) {
+        let js_code = "if (window.playSound) { window.playSound('swap'); }";
+        js_sys::eval(js_code).unwrap_or(wasm_bindgen::JsValue::UNDEFINED);
+    }
+
+    #[wasm_bindgen]
+    pub fn play_win_sound(&self) {
+        let js_code = "if (window.playSound) { window.playSound('hs'); }";
+        js_sys::eval(js_code).unwrap_or(wasm_bindgen::JsValue::UNDEFINED);
     }
 
     #[wasm_bindgen]
     pub fn play_crash_sound(&self) {
         let js_code = "if (window.playCrashSound) { window.playCrashSound(); }";
-        js_sys::eval(js_code).unwrap_or_else(|_| wasm_bindgen::JsValue::UNDEFINED);
+        js_sys::eval(js_code).unwrap_or(wasm_bindgen::JsValue::UNDEFINED);
     }
 
 
     #[wasm_bindgen]
     pub fn stop_music(&self) {
         let js_code = "if (window.stopMusic) { window.stopMusic(); }";
-        js_sys::eval(js_code).unwrap_or_else(|_| wasm_bindgen::JsValue::UNDEFINED);
+        js_sys::eval(js_code).unwrap_or(wasm_bindgen::JsValue::UNDEFINED);
     }
 
     #[wasm_bindgen]
     pub fn pause_music(&self) {
         let js_code = "if (window.pauseMusic) { window.pauseMusic(); }";
-        js_sys::eval(js_code).unwrap_or_else(|_| wasm_bindgen::JsValue::UNDEFINED);
+        js_sys::eval(js_code).unwrap_or(wasm_bindgen::JsValue::UNDEFINED);
     }
 
     #[wasm_bindgen]
     pub fn resume_music(&self) {
         let js_code = "if (window.resumeMusic) { window.resumeMusic(); }";
-        js_sys::eval(js_code).unwrap_or_else(|_| wasm_bindgen::JsValue::UNDEFINED);
-    }
-
-    fn restart_game(&mut self) {
-        // Stop any playing music and restart background music
-        self.stop_music();
-        
-        state::core::tick::restart_game(
-            &mut self.player,
-            &mut self.food,
-            &mut self.loot_crate,
-            &mut self.score,
-            &mut self.game_over,
-            &mut self.last_frame_time,
-            &mut self.stars_offset_x,
-            &mut self.stars_sprite_frame_index,
-            &mut self.stars_last_sprite_frame_update_time,
-            &mut self.globe_sprite_frame_index,
-            &mut self.globe_last_sprite_frame_update_time,
-            &mut self.game_over_frame,
-            &mut self.game_over_darkness,
-            &mut self.game_over_animation_time,
-            &mut self.last_loot_spawn_score,
-            &mut self.powerup_eligibility,
-            &mut self.selected_powerup,
-            &mut self.food_score_value,
-            &mut self.in_powerup_selection,
-            &mut self.highlighted_powerup,
-            &mut self.powerup_selection_keys,
-            &mut self.last_loot_crate_check_time,
-        );
-        
-        // Reset crash sound state
-        self.crash_sound_played = false;
-        
-        // Resume background music after restart
-        self.resume_music();
-    }
-
-    fn handle_powerup_selection(&mut self) {
-        if state::core::perks::handle_powerup_selection(
-            &mut self.powerup_selection_keys,
-            &mut self.highlighted_powerup,
-            &mut self.selected_powerup,
-            &mut self.powerup_eligibility,
-            &mut self.in_powerup_selection,
-        ) {
-            // A powerup was selected, apply its effect
-            if let Some(ref powerup) = self.selected_powerup {
-                state::core::perks::apply_powerup_effect(powerup, &mut self.player.move_interval, &mut self.food_score_value);
-                
-                // Play special sound for each powerup
-                match powerup {
-                    state::core::perks::Perk::HungryWorm => {
-                        web_sys::console::log_1(&"Hungry Worm selected, playing apple sound".into());
-                        self.play_powerup_sound("apple"); // This will resume music when sound ends
-                    }
-                    state::core::perks::Perk::NeedForSpeed => {
-                        web_sys::console::log_1(&"Need 4 Speed selected, playing turbo sound".into());
-                        self.play_powerup_sound("turbo"); // This will resume music when sound ends
-                    }
-                }
-            } else {
-                // No powerup selected, resume music
-                web_sys::console::log_1(&"No powerup selected, resuming music".into());
-                self.resume_music();
-            }
-        }
+        js_sys::eval(js_code).unwrap_or(wasm_bindgen::JsValue::UNDEFINED);
     }
-
-
-    fn update_game_over_animation(&mut self) {
-        if state::core::tick::update_game_over_animation(
-            &mut self.game_over_frame,
-            &mut self.game_over_darkness,
-            &mut self.game_over_animation_time,
-        ) {
-            self.restart_game();
-        }
-    }
-
-
-
-
-}
\ No newline at end of file
+}
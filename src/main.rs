@@ -1,3 +1,63 @@
-fn main() {
-    println!("This is a WASM-only project. Use wasm-pack to build for web.");
-}
\ No newline at end of file
+use std::process::ExitCode;
+
+use space_worm::state::replay::Replay;
+use space_worm::state::simulation::Simulation;
+
+// Replay verifier: plays a recorded run back headlessly and checks the claimed score.
+// The game itself is WASM-only; use wasm-pack to build it for the web.
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().collect();
+    let Some(path) = args.get(1) else {
+        eprintln!("Usage: {} <replay-file>", args.first().map(String::as_str).unwrap_or("space_worm"));
+        return ExitCode::from(2);
+    };
+
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", path, e);
+            return ExitCode::from(2);
+        }
+    };
+
+    let replay = match Replay::from_bytes(&bytes) {
+        Ok(replay) => replay,
+        Err(e) => {
+            eprintln!("Failed to load replay {}: {}", path, e);
+            return ExitCode::from(2);
+        }
+    };
+
+    let outcome = Simulation::run_replay(&replay);
+
+    println!("Seed:           {}", replay.seed);
+    println!("Level:          {}", replay.level.name);
+    println!("Final score:    {}", outcome.score);
+    println!("Worm length:    {}", outcome.length);
+    println!("Ticks survived: {}", outcome.ticks);
+    let ending = match (outcome.game_over, outcome.won) {
+        (true, true) => "board cleared",
+        (true, false) => "crashed",
+        (false, _) => "still running",
+    };
+    println!("Outcome:        {}", ending);
+    if outcome.perks.is_empty() {
+        println!("Perks chosen:   none");
+    } else {
+        println!("Perks chosen:");
+        for (tick, perk) in &outcome.perks {
+            println!("  tick {:>7}  {}", tick, perk.name());
+        }
+    }
+
+    if outcome.score != replay.score || outcome.ticks != replay.ticks {
+        eprintln!(
+            "MISMATCH: replay claims score {} after {} ticks, simulation produced {} after {} ticks",
+            replay.score, replay.ticks, outcome.score, outcome.ticks
+        );
+        return ExitCode::FAILURE;
+    }
+
+    println!("OK: claimed score {} verified", replay.score);
+    ExitCode::SUCCESS
+}
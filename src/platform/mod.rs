@@ -1,7 +1,9 @@
 pub mod web;
+pub mod native;
 
 // Re-export platform-specific modules
 pub use web::*;
+pub use native::*;
 
 // Common traits and types that both platforms must implement
 pub trait PlatformAudio {
@@ -31,6 +33,11 @@ pub trait PlatformInput {
     fn is_mouse_button_pressed(&self, button: MouseButton) -> bool;
 }
 
+/// Time source for the simulation. Values are in milliseconds from an arbitrary, fixed origin.
+pub trait PlatformClock {
+    fn now_ms(&self) -> f64;
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum VirtualKey {
     Up,
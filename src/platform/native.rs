@@ -0,0 +1,47 @@
+use super::PlatformClock;
+use std::time::Instant;
+
+/// Wall clock for native builds, measured from the moment the clock was created.
+pub struct NativeClock {
+    origin: Instant,
+}
+
+impl NativeClock {
+    pub fn new() -> Self {
+        Self { origin: Instant::now() }
+    }
+}
+
+impl Default for NativeClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PlatformClock for NativeClock {
+    fn now_ms(&self) -> f64 {
+        self.origin.elapsed().as_secs_f64() * 1000.0
+    }
+}
+
+/// Clock that only moves when told to, for headless runs and tests.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ManualClock {
+    now: f64,
+}
+
+impl ManualClock {
+    pub fn new(start_ms: f64) -> Self {
+        Self { now: start_ms }
+    }
+
+    pub fn advance(&mut self, ms: f64) {
+        self.now += ms;
+    }
+}
+
+impl PlatformClock for ManualClock {
+    fn now_ms(&self) -> f64 {
+        self.now
+    }
+}
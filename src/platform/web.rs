@@ -1,4 +1,4 @@
-use super::{PlatformAudio, PlatformWindow, PlatformInput, VirtualKey, MouseButton};
+use super::{PlatformAudio, PlatformWindow, PlatformInput, PlatformClock, VirtualKey, MouseButton};
 use crate::audio::{MusicId, SfxId};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
@@ -44,7 +44,7 @@ impl PlatformAudio for WebAudio {
         if let Some(buffer) = self.music_buffers.get(&music_id) {
             // Stop current music if playing
             if let Some(ref source) = self.current_music_source {
-                source.stop()?;
+                AudioScheduledSourceNode::stop(source)?;
             }
             
             let source = self.audio_context.create_buffer_source()?;
@@ -81,7 +81,7 @@ impl PlatformAudio for WebAudio {
     
     fn stop_music(&self) {
         if let Some(ref source) = self.current_music_source {
-            let _ = source.stop();
+            let _ = AudioScheduledSourceNode::stop(source);
         }
     }
     
@@ -97,6 +97,32 @@ impl PlatformAudio for WebAudio {
     }
 }
 
+pub struct WebClock;
+
+impl PlatformClock for WebClock {
+    fn now_ms(&self) -> f64 {
+        js_sys::Date::now()
+    }
+}
+
+/// Small values kept in the browser's localStorage so they outlive the page. Failures (private
+/// browsing, storage disabled) are treated as nothing stored.
+pub struct WebStorage;
+
+impl WebStorage {
+    pub fn get(key: &str) -> Option<String> {
+        window()?.local_storage().ok()??.get_item(key).ok()?
+    }
+
+    pub fn set(key: &str, value: &str) {
+        if let Some(Ok(Some(storage))) = window().map(|window| window.local_storage()) {
+            if storage.set_item(key, value).is_err() {
+                console_log!("Could not store {}", key);
+            }
+        }
+    }
+}
+
 pub struct WebWindow {
     pub canvas: HtmlCanvasElement,
     context: CanvasRenderingContext2d,
@@ -174,6 +200,7 @@ pub struct WebInput {
     pressed_keys: Rc<RefCell<HashMap<VirtualKey, bool>>>,
     mouse_pos: Rc<RefCell<Option<(f32, f32)>>>,
     mouse_buttons: Rc<RefCell<HashMap<MouseButton, bool>>>,
+    clicks: Rc<RefCell<Vec<(f32, f32)>>>, // Clicks and taps since the last `take_clicks`, in canvas pixels
 }
 
 impl WebInput {
@@ -181,6 +208,7 @@ impl WebInput {
         let pressed_keys = Rc::new(RefCell::new(HashMap::new()));
         let mouse_pos = Rc::new(RefCell::new(None));
         let mouse_buttons = Rc::new(RefCell::new(HashMap::new()));
+        let clicks = Rc::new(RefCell::new(Vec::new()));
         
         // Set up keyboard event listeners
         {
@@ -220,12 +248,45 @@ impl WebInput {
             mousemove_closure.forget();
         }
         
+        for (event_name, pressed) in [("mousedown", true), ("mouseup", false)] {
+            let mouse_buttons_clone = mouse_buttons.clone();
+            let button_closure = Closure::wrap(Box::new(move |event: MouseEvent| {
+                if let Some(button) = mouse_button_from_index(event.button()) {
+                    mouse_buttons_clone.borrow_mut().insert(button, pressed);
+                }
+            }) as Box<dyn FnMut(_)>);
+            
+            canvas.add_event_listener_with_callback(event_name, button_closure.as_ref().unchecked_ref())?;
+            button_closure.forget();
+        }
+        
+        // Clicks fire for mouse clicks and for taps, but not for swipes
+        {
+            let clicks_clone = clicks.clone();
+            let canvas_clone = canvas.clone();
+            let click_closure = Closure::wrap(Box::new(move |event: MouseEvent| {
+                // Offsets are in CSS pixels; the canvas may be displayed at a different size
+                let x_scale = canvas_clone.width() as f32 / canvas_clone.client_width().max(1) as f32;
+                let y_scale = canvas_clone.height() as f32 / canvas_clone.client_height().max(1) as f32;
+                clicks_clone.borrow_mut().push((event.offset_x() as f32 * x_scale, event.offset_y() as f32 * y_scale));
+            }) as Box<dyn FnMut(_)>);
+            
+            canvas.add_event_listener_with_callback("click", click_closure.as_ref().unchecked_ref())?;
+            click_closure.forget();
+        }
+        
         Ok(Self {
             pressed_keys,
             mouse_pos,
             mouse_buttons,
+            clicks,
         })
     }
+    
+    /// Takes the clicks and taps made since the last call, in canvas pixel coordinates.
+    pub fn take_clicks(&self) -> Vec<(f32, f32)> {
+        std::mem::take(&mut *self.clicks.borrow_mut())
+    }
 }
 
 impl PlatformInput for WebInput {
@@ -242,6 +303,15 @@ impl PlatformInput for WebInput {
     }
 }
 
+fn mouse_button_from_index(index: i16) -> Option<MouseButton> {
+    match index {
+        0 => Some(MouseButton::Left),
+        1 => Some(MouseButton::Middle),
+        2 => Some(MouseButton::Right),
+        _ => None,
+    }
+}
+
 fn key_code_to_virtual(code: &str) -> Option<VirtualKey> {
     match code {
         "KeyW" => Some(VirtualKey::W),
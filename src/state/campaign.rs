@@ -0,0 +1,40 @@
+use std::sync::OnceLock;
+
+use crate::state::level::Level;
+
+/// The campaign's stages in order, written in the level text format. Every stage has a goal;
+/// meeting it unlocks the next one.
+pub const STAGES: [&str; 5] = [
+    include_str!("../../assets/campaign/01_first_flight.txt"),
+    include_str!("../../assets/campaign/02_growth_spurt.txt"),
+    include_str!("../../assets/campaign/03_hold_out.txt"),
+    include_str!("../../assets/campaign/04_station_run.txt"),
+    include_str!("../../assets/campaign/05_deep_belt.txt"),
+];
+
+/// Campaign stage `index` (counted from 0), if there is one.
+pub fn stage(index: usize) -> Option<Level> {
+    parsed_stages().get(index).cloned()
+}
+
+/// Name of campaign stage `index`, for screens that show it every frame.
+pub fn stage_name(index: usize) -> Option<&'static str> {
+    parsed_stages().get(index).map(|level| level.name.as_str())
+}
+
+// The stage texts are parsed once, the first time any stage is needed
+fn parsed_stages() -> &'static [Level] {
+    static PARSED: OnceLock<Vec<Level>> = OnceLock::new();
+    PARSED.get_or_init(|| {
+        STAGES
+            .iter()
+            .map(|text| Level::parse(text).expect("campaign stages are valid"))
+            .collect()
+    })
+}
+
+/// Highest stage unlocked after clearing stage `cleared`. Clearing the last stage keeps it as
+/// the highest.
+pub fn unlocked_after(cleared: usize) -> usize {
+    (cleared + 1).min(STAGES.len() - 1)
+}
@@ -0,0 +1,112 @@
+use wasm_bindgen::prelude::*;
+
+use crate::state::codec::{ByteReader, ByteWriter, DecodeError};
+use crate::state::constants::state::{MAX_FOOD_COUNT, MIN_DECAY_FACTOR};
+use crate::state::constants::timing::TICKS_PER_SECOND;
+use crate::state::core::difficulty::{Difficulty, DIFFICULTIES};
+
+/// How food loses value over its lifetime.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecayCurve {
+    Linear,      // Loses value at a steady rate
+    Exponential, // Halves every quarter of the lifetime, so early bites pay the most
+    Stepped,     // Drops a quarter at a time
+}
+
+impl DecayCurve {
+    /// Share of the full value left after `progress` (0.0 fresh to 1.0 expired) of the lifetime.
+    pub fn factor(self, progress: f32) -> f32 {
+        let progress = progress.clamp(0.0, 1.0);
+        let factor = match self {
+            DecayCurve::Linear => 1.0 - progress,
+            DecayCurve::Exponential => 0.5f32.powf(4.0 * progress),
+            DecayCurve::Stepped => 1.0 - (4.0 * progress).floor() / 4.0,
+        };
+        factor.max(MIN_DECAY_FACTOR)
+    }
+}
+
+/// Rules chosen when a run starts. They change how a seed plays out, so they are stored in
+/// replays and save files alongside the seed.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameConfig {
+    pub grid_mode: bool, // Snake moves between discrete cells instead of free float positions
+    pub walls: bool, // Leaving the playfield ends the run instead of wrapping to the other side
+    pub food_count: u8, // Food items on the board at once, 1 to MAX_FOOD_COUNT
+    pub food_lifetime: u32, // Seconds before uneaten food moves elsewhere; 0 keeps it forever
+    pub decay: DecayCurve, // How expiring food loses value
+    pub difficulty: Difficulty, // Starting speed and how fast the run ramps up
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            grid_mode: false,
+            walls: false,
+            food_count: 1,
+            food_lifetime: 0,
+            decay: DecayCurve::Linear,
+            difficulty: Difficulty::Normal,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl GameConfig {
+    /// The default rules: free movement, wrapping edges, one food at a time that never expires,
+    /// on Normal difficulty.
+    /// Set fields before passing it to `WasmGame`.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> GameConfig {
+        GameConfig::default()
+    }
+}
+
+impl GameConfig {
+    /// How many food items to keep on the board, with out-of-range settings pulled into range.
+    pub fn food_items(&self) -> usize {
+        self.food_count.clamp(1, MAX_FOOD_COUNT) as usize
+    }
+
+    /// Ticks food lasts before it moves, if food expires at all.
+    pub fn food_lifetime_ticks(&self) -> Option<u64> {
+        (self.food_lifetime > 0).then(|| self.food_lifetime as u64 * TICKS_PER_SECOND)
+    }
+
+    pub fn write(&self, writer: &mut ByteWriter) {
+        writer.bool(self.grid_mode);
+        writer.bool(self.walls);
+        writer.u8(self.food_count);
+        writer.varint(self.food_lifetime as u64);
+        writer.u8(match self.decay {
+            DecayCurve::Linear => 0,
+            DecayCurve::Exponential => 1,
+            DecayCurve::Stepped => 2,
+        });
+        writer.u8(self.difficulty.index() as u8);
+    }
+
+    pub fn read(reader: &mut ByteReader) -> Result<Self, DecodeError> {
+        Ok(GameConfig {
+            grid_mode: reader.bool()?,
+            walls: reader.bool()?,
+            food_count: match reader.u8()? {
+                count @ 1..=MAX_FOOD_COUNT => count,
+                _ => return Err(DecodeError::InvalidValue("food count")),
+            },
+            food_lifetime: reader.varint()? as u32,
+            decay: match reader.u8()? {
+                0 => DecayCurve::Linear,
+                1 => DecayCurve::Exponential,
+                2 => DecayCurve::Stepped,
+                _ => return Err(DecodeError::InvalidValue("decay curve")),
+            },
+            difficulty: DIFFICULTIES
+                .get(reader.u8()? as usize)
+                .map(|definition| definition.difficulty)
+                .ok_or(DecodeError::InvalidValue("difficulty"))?,
+        })
+    }
+}
@@ -7,6 +7,44 @@ pub mod graphics {
     pub const SNAKE_BODY_HEIGHT: f32 = 8.0;
 }
 
+pub mod grid {
+    use super::graphics::{ART_HEIGHT, ART_WIDTH, SNAKE_BODY_HEIGHT, SNAKE_BODY_WIDTH};
+
+    // Grid mode divides the playfield into cells the size of one body segment
+    pub const CELL_WIDTH: f32 = SNAKE_BODY_WIDTH;
+    pub const CELL_HEIGHT: f32 = SNAKE_BODY_HEIGHT;
+    pub const GRID_COLUMNS: i32 = ART_WIDTH as i32 / CELL_WIDTH as i32; // 42
+    pub const GRID_ROWS: i32 = ART_HEIGHT as i32 / CELL_HEIGHT as i32; // 28
+    pub const HUD_ROWS: i32 = 3; // Rows hidden under the HUD strip
+}
+
+pub mod level {
+    use super::grid::{GRID_ROWS, HUD_ROWS};
+
+    pub const MAP_ROWS: i32 = GRID_ROWS - HUD_ROWS; // A level map covers the playfield below the HUD
+    pub const DEFAULT_SPEED: u32 = 10; // Moves per second
+    pub const MAX_SPEED: u32 = 60;
+    pub const SPAWN_ROOM: usize = 3; // Free cells needed behind the spawn point for the worm's body
+}
+
+pub mod difficulty {
+    pub const RAMP_SCORE_STEP: u32 = 1000; // The run gets harder every 1000 points...
+    pub const RAMP_LENGTH_STEP: usize = 10; // ...and every 10 segments grown
+}
+
+pub mod food {
+    pub const ROTTEN_SHRINK: usize = 2; // Segments rotten food takes off the tail
+    pub const MIN_SNAKE_LENGTH: usize = 3; // Rotten food never shrinks the worm below its starting length
+    pub const SPICY_SPEED_FACTOR: f32 = 0.75; // Spicy food shortens the move interval like one Need 4 Speed stack
+}
+
+pub mod placement {
+    pub const ITEM_SIZE: f32 = 16.0; // Food and loot crate sprites are 16x16
+    pub const HUD_HEIGHT: f32 = 24.0; // Top strip reserved for the score and other HUD text
+    pub const EAT_RADIUS: f32 = 12.0; // Items closer than this to the head are eaten
+    pub const HEAD_CLEARANCE: f32 = 36.0; // Preferred minimum distance between a new item and the head
+}
+
 pub mod physics {
     pub const LOWER_BOUND_X: f32 = 2.0;
     pub const UPPER_BOUND_X: f32 = 256.0;
@@ -18,8 +56,15 @@ pub mod physics {
 pub mod text {
     pub const SCORE: &str = "Score: ";
     pub const SELECT_POWERUP: &str = "Select Powerup";
-    pub const POWERUP_NEED_4_SPEED: (&str, &str) = ("Need 4 Speed", "+25% movement speed");
-    pub const POWERUP_HUNGRY_WORM: (&str, &str) = ("Hungry Worm", "2x score from food");
+    pub const POWERUP_NEED_4_SPEED: (&str, &str) = ("Need 4 Speed", "+25% speed, 20 sec");
+    pub const POWERUP_HUNGRY_WORM: (&str, &str) = ("Hungry Worm", "2x score for 30 sec");
+    pub const POWERUP_REVERSO: (&str, &str) = ("Reverso", "Tail becomes head");
+    pub const POWERUP_SWAP: (&str, &str) = ("Swap", "E: jump to the food");
+    pub const PAUSED: &str = "PAUSED";
+    pub const BOARD_CLEARED: &str = "YOU WIN";
+    pub const STAGE_CLEAR: &str = "STAGE CLEAR";
+    pub const CAMPAIGN_COMPLETE: &str = "Campaign complete!";
+    pub const PAUSED_HINT: &str = "Esc to resume";
 }
 
 pub mod audio {
@@ -32,6 +77,8 @@ pub mod audio {
     pub const NEW_POWERUP_FILE: &str = "assets/audio/new_perk.mp3";
     pub const NEED_FOR_SPEED_POWERUP_CHOSEN_FILE: &str = "assets/audio/turbo.mp3";
     pub const HUNGRY_WORM_POWERUP_CHOSEN_FILE: &str = "assets/audio/hungry_worm.mp3";
+    pub const REVERSO_POWERUP_CHOSEN_FILE: &str = "assets/audio/reverso.mp3";
+    pub const SWAP_FILE: &str = "assets/audio/swap.mp3";
 
     pub const SNAKE_EAT_FOOD_FILE: &str = "assets/audio/eat.mp3";
     pub const GAME_OVER_FILE: &str = "assets/audio/game_over.mp3";
@@ -41,5 +88,32 @@ pub mod state {
     pub const FRAME_RATE_SLEEP_DURATION: u64 = 16; // 16 ms for ~60 FPS
     pub const LOOT_CRATE_SPAWN_INTERVAL: u32 = 100; // Every 100 points, check for loot crate spawn
     pub const LOOT_CRATE_SPAWN_CHANCE: u8 = 20; // 20% chance to spawn loot crate
+    pub const BASE_MOVE_INTERVAL: f32 = 0.1; // Seconds between moves without perks, 10 moves per second
+    pub const BASE_FOOD_SCORE_VALUE: u32 = 100; // Points per food without perks
+    pub const SWAP_CHARGES_PER_PICK: u32 = 2; // Swaps granted each time the Swap perk is picked
+    pub const THIRD_OFFER_CHANCE: u32 = 30; // 30% of loot crates offer three perks instead of two
+    pub const MAX_FOOD_COUNT: u8 = 5; // Most food items a run can keep on the board at once
+    pub const MIN_DECAY_FACTOR: f32 = 0.1; // Expiring food is always worth at least 10% of its value
+    pub const MAX_COMBO: u32 = 9; // Highest combo multiplier, keeps the HUD label to one digit
+}
+
+pub mod timing {
+    pub const TICKS_PER_SECOND: u64 = 120; // Fixed logic rate, independent of the display refresh rate
+    pub const TICK_DURATION_MS: f64 = 1000.0 / TICKS_PER_SECOND as f64;
+    pub const MAX_FRAME_TIME_MS: f64 = 250.0; // Longer frames (throttled tabs) are clamped instead of fast-forwarded
+    pub const STARS_SCROLL_TICKS: u64 = 2; // Parallax scrolls one unit every 2 ticks (60 units/s)
+    pub const STARS_FRAME_TICKS: u64 = 30; // 250 ms
+    pub const GLOBE_FRAME_TICKS: u64 = 120; // 1000 ms
+    pub const HEAD_FRAME_TICKS: u64 = 60; // 500 ms
+    pub const FOOD_FRAME_TICKS: u64 = 60; // 500 ms
+    pub const LOOT_CRATE_FRAME_TICKS: u64 = 90; // 750 ms
+    pub const LOOT_CRATE_CHECK_TICKS: u64 = 1200; // 10 s
+    pub const GAME_OVER_FRAME_TICKS: u64 = 60; // 500 ms
+    pub const NEED_FOR_SPEED_TICKS: u64 = 2400; // 20 s
+    pub const HUNGRY_WORM_TICKS: u64 = 3600; // 30 s
+    pub const SPICY_TICKS: u64 = 360; // 3 s burst of speed from spicy food
+    pub const FOOD_BLINK_TICKS: u64 = 240; // Expiring food blinks for its last 2 s
+    pub const FOOD_BLINK_PERIOD_TICKS: u64 = 15; // 125 ms on, 125 ms off
+    pub const COMBO_WINDOW_TICKS: u64 = 480; // 4 s to take the next bite and keep a combo going
 }
 
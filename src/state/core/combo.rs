@@ -0,0 +1,44 @@
+use crate::state::constants::state::MAX_COMBO;
+use crate::state::constants::timing::COMBO_WINDOW_TICKS;
+
+/// A streak of bites, each eaten within the combo window of the one before. Every bite in a
+/// streak scores one step higher than the last, up to `MAX_COMBO` times its points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Combo {
+    pub streak: u32,          // Bites in the current streak, 0 when none is running
+    pub remaining_ticks: u64, // Time left to extend the streak
+    pub best: u32,            // Highest multiplier reached this run
+}
+
+impl Combo {
+    /// Multiplier the current streak has reached (1 without a streak).
+    pub fn multiplier(&self) -> u32 {
+        self.streak.clamp(1, MAX_COMBO)
+    }
+
+    /// Multiplier the next bite scores at, if it comes in time.
+    pub fn next_multiplier(&self) -> u32 {
+        (self.streak + 1).min(MAX_COMBO)
+    }
+
+    /// Extends the streak and restarts the window.
+    pub fn record_bite(&mut self) {
+        self.streak += 1;
+        self.remaining_ticks = COMBO_WINDOW_TICKS;
+        self.best = self.best.max(self.multiplier());
+    }
+
+    /// Ends the streak on the spot. The best combo of the run is kept.
+    pub fn reset(&mut self) {
+        self.streak = 0;
+        self.remaining_ticks = 0;
+    }
+
+    /// Counts the window down; the streak ends when it lapses.
+    pub fn update(&mut self) {
+        self.remaining_ticks = self.remaining_ticks.saturating_sub(1);
+        if self.remaining_ticks == 0 {
+            self.streak = 0;
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use wasm_bindgen::prelude::*;
+
+use crate::state::constants::difficulty::{RAMP_LENGTH_STEP, RAMP_SCORE_STEP};
+use crate::state::constants::food::MIN_SNAKE_LENGTH;
+use crate::state::constants::state::LOOT_CRATE_SPAWN_CHANCE;
+use crate::state::grid::Cell;
+use crate::state::level::{Obstacle, ObstacleKind};
+use crate::state::structs::GameState;
+
+/// How hard a run starts and how quickly it gets harder.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Insane,
+}
+
+/// Everything the difficulty controller needs to know about one preset. A run ramps up one step
+/// for every `RAMP_SCORE_STEP` points and every `RAMP_LENGTH_STEP` segments grown, so shrinking
+/// the worm with rotten food eases it off again.
+pub struct DifficultyDefinition {
+    pub difficulty: Difficulty,
+    pub id: &'static str,    // Stable name used for the high score table
+    pub label: &'static str, // Shown on the game over screen
+    pub start_speed: f32,    // Speed factor on top of the level's speed at the start of a run
+    pub speed_step: f32,     // Speed factor added per ramp step
+    pub max_speed: f32,
+    pub crate_chance: u32, // % chance of a loot crate at each check at the start of a run
+    pub crate_step: u32,   // Percentage points lost per ramp step
+    pub min_crate_chance: u32,
+    pub hazards_per_step: usize, // Asteroids that drift onto the board per ramp step
+    pub max_hazards: usize,
+}
+
+/// Every preset, from gentlest to harshest. Normal starts out as the classic game (base speed,
+/// the usual crate odds, no asteroids) and ramps up gently. Easy only speeds up a little and never
+/// adds asteroids; Hard and Insane start faster and ramp up harder.
+pub const DIFFICULTIES: [DifficultyDefinition; 4] = [
+    DifficultyDefinition {
+        difficulty: Difficulty::Easy,
+        id: "easy",
+        label: "EASY",
+        start_speed: 0.8,
+        speed_step: 0.02,
+        max_speed: 1.0,
+        crate_chance: 35,
+        crate_step: 0,
+        min_crate_chance: 35,
+        hazards_per_step: 0,
+        max_hazards: 0,
+    },
+    DifficultyDefinition {
+        difficulty: Difficulty::Normal,
+        id: "normal",
+        label: "NORMAL",
+        start_speed: 1.0,
+        speed_step: 0.03,
+        max_speed: 1.3,
+        crate_chance: LOOT_CRATE_SPAWN_CHANCE as u32,
+        crate_step: 1,
+        min_crate_chance: 12,
+        hazards_per_step: 1,
+        max_hazards: 4,
+    },
+    DifficultyDefinition {
+        difficulty: Difficulty::Hard,
+        id: "hard",
+        label: "HARD",
+        start_speed: 1.2,
+        speed_step: 0.08,
+        max_speed: 2.0,
+        crate_chance: 25,
+        crate_step: 2,
+        min_crate_chance: 10,
+        hazards_per_step: 2,
+        max_hazards: 12,
+    },
+    DifficultyDefinition {
+        difficulty: Difficulty::Insane,
+        id: "insane",
+        label: "INSANE",
+        start_speed: 1.5,
+        speed_step: 0.1,
+        max_speed: 2.5,
+        crate_chance: 20,
+        crate_step: 3,
+        min_crate_chance: 5,
+        hazards_per_step: 3,
+        max_hazards: 20,
+    },
+];
+
+impl Difficulty {
+    pub fn definition(self) -> &'static DifficultyDefinition {
+        &DIFFICULTIES[self.index()]
+    }
+
+    /// Position in `DIFFICULTIES`, also used to store the preset in save files.
+    pub fn index(self) -> usize {
+        DIFFICULTIES
+            .iter()
+            .position(|definition| definition.difficulty == self)
+            .expect("every difficulty has a definition")
+    }
+
+    pub fn from_id(id: &str) -> Option<Difficulty> {
+        DIFFICULTIES.iter().find(|definition| definition.id == id).map(|definition| definition.difficulty)
+    }
+}
+
+impl DifficultyDefinition {
+    /// Speed factor at ramp step `step`.
+    pub fn speed(&self, step: u32) -> f32 {
+        (self.start_speed + self.speed_step * step as f32).min(self.max_speed)
+    }
+
+    /// % chance of a loot crate at each check at ramp step `step`.
+    pub fn crate_chance(&self, step: u32) -> u32 {
+        self.crate_chance.saturating_sub(self.crate_step * step).max(self.min_crate_chance)
+    }
+
+    /// Asteroids the ramp has added to the board by ramp step `step`.
+    pub fn hazards(&self, step: u32) -> usize {
+        (self.hazards_per_step * step as usize).min(self.max_hazards)
+    }
+}
+
+/// How far a run has ramped up, from its score and the worm's length.
+pub fn ramp_step(score: u32, length: usize) -> u32 {
+    score / RAMP_SCORE_STEP + (length.saturating_sub(MIN_SNAKE_LENGTH) / RAMP_LENGTH_STEP) as u32
+}
+
+/// Ramp step of the run in progress.
+pub fn current_step(state: &GameState) -> u32 {
+    ramp_step(state.score, state.player.body.len())
+}
+
+/// The preset shaping the run in progress. Campaign stages are tuned by hand and play the same on
+/// every preset, so they have none: no speed factor, the classic crate odds and no asteroids.
+pub fn active_definition(state: &GameState) -> Option<&'static DifficultyDefinition> {
+    match state.campaign_stage {
+        Some(_) => None,
+        None => Some(state.config.difficulty.definition()),
+    }
+}
+
+/// % chance of a loot crate at each check of the run in progress.
+pub fn crate_chance(state: &GameState) -> u32 {
+    active_definition(state).map_or(LOOT_CRATE_SPAWN_CHANCE as u32, |definition| {
+        definition.crate_chance(current_step(state))
+    })
+}
+
+/// Drops asteroids onto free spots, clear of the worm's head and the items on the board, until
+/// the board holds as many as the run's ramp step calls for.
+pub fn spawn_hazards(state: &mut GameState) {
+    let target = active_definition(state).map_or(0, |definition| definition.hazards(current_step(state)));
+    while state.hazards < target {
+        let avoid = crate::state::placement::occupied_positions(&state.food, &state.loot_crate, None);
+        let Some(position) = crate::state::placement::find_free_position(
+            &state.player,
+            &avoid,
+            &[],
+            &state.level.obstacles,
+            state.config.grid_mode,
+            &mut state.rng,
+        ) else {
+            return; // No room left; try again next tick
+        };
+        state.level.obstacles.push(Obstacle { kind: ObstacleKind::Asteroid, cell: Cell::of(position) });
+        state.hazards += 1;
+    }
+}
@@ -0,0 +1,74 @@
+use crate::state::constants::food::SPICY_SPEED_FACTOR;
+use crate::state::core::perks::{Perk, PerkEffect};
+use crate::state::structs::GameState;
+
+/// What happens when a perk that is already active is picked again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stacking {
+    Refresh,  // The timer restarts; the effect never gets stronger
+    Add,      // The full duration is added to the time left
+    Cap(u32), // One more stack, up to the limit, and the timer restarts
+}
+
+/// A perk currently modifying the worm's stats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActiveEffect {
+    pub perk: Perk,
+    pub stacks: u32,
+    pub remaining_ticks: Option<u64>, // None = permanent
+}
+
+/// Starts `perk`'s effect, or stacks it onto the one already running.
+pub fn add_effect(effects: &mut Vec<ActiveEffect>, perk: Perk) {
+    let PerkEffect::Modifier { duration, stacking, .. } = perk.definition().effect else {
+        return; // Instant perks have nothing to keep track of
+    };
+
+    let Some(existing) = effects.iter_mut().find(|effect| effect.perk == perk) else {
+        effects.push(ActiveEffect { perk, stacks: 1, remaining_ticks: duration });
+        return;
+    };
+
+    match stacking {
+        Stacking::Refresh => existing.remaining_ticks = duration,
+        Stacking::Add => {
+            existing.remaining_ticks = existing.remaining_ticks.zip(duration).map(|(left, extra)| left + extra);
+        }
+        Stacking::Cap(max_stacks) => {
+            existing.stacks = (existing.stacks + 1).min(max_stacks);
+            existing.remaining_ticks = duration;
+        }
+    }
+}
+
+/// Counts every timed effect down by one tick and drops the ones that ran out.
+pub fn update_active_effects(effects: &mut Vec<ActiveEffect>) {
+    for effect in effects.iter_mut() {
+        if let Some(remaining) = effect.remaining_ticks.as_mut() {
+            *remaining = remaining.saturating_sub(1);
+        }
+    }
+    effects.retain(|effect| effect.remaining_ticks != Some(0));
+}
+
+/// Rebuilds the worm's speed and the food value from the base stats plus every active effect,
+/// so perks wear off cleanly instead of leaving their changes behind.
+pub fn recalculate_stats(state: &mut GameState) {
+    let mut stats = state.base_stats;
+    for effect in &state.active_effects {
+        if let PerkEffect::Modifier { modify, .. } = effect.perk.definition().effect {
+            modify(&mut stats, effect.stacks);
+        }
+    }
+
+    // A spicy bite is a burst of speed of its own, on top of any perks
+    if state.spicy_ticks > 0 {
+        stats.move_interval *= SPICY_SPEED_FACTOR;
+    }
+
+    // The difficulty ramp speeds the worm up on top of its perks
+    stats.move_interval /= state.difficulty_speed();
+
+    state.player.move_interval = stats.move_interval;
+    state.food_score_value = stats.food_score_value;
+}
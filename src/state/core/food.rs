@@ -1 +1,123 @@
-// This file contained desktop-specific logic that has been removed for WASM-only build
+use crate::state::constants::food::{MIN_SNAKE_LENGTH, ROTTEN_SHRINK};
+use crate::state::config::GameConfig;
+use crate::state::constants::timing::{FOOD_BLINK_PERIOD_TICKS, FOOD_BLINK_TICKS, SPICY_TICKS};
+use crate::state::core::effects;
+use crate::state::core::perks;
+use crate::state::rng::SeededRng;
+use crate::state::structs::{Food, GameState, Snake};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FoodKind {
+    Standard,
+    Golden,
+    Rotten,
+    Spicy,
+    Cosmic,
+}
+
+/// Everything the game needs to know about one kind of food.
+pub struct FoodDefinition {
+    pub kind: FoodKind,
+    pub id: &'static str, // Stable name used in save files
+    pub weight: u64,      // Relative chance of spawning
+    pub score_multiplier: u32,
+}
+
+/// Every food kind. Standard food is by far the most common; cosmic food is a rare treat.
+pub const FOOD_KINDS: [FoodDefinition; 5] = [
+    FoodDefinition { kind: FoodKind::Standard, id: "standard", weight: 70, score_multiplier: 1 },
+    FoodDefinition { kind: FoodKind::Golden, id: "golden", weight: 10, score_multiplier: 5 },
+    FoodDefinition { kind: FoodKind::Rotten, id: "rotten", weight: 10, score_multiplier: 0 },
+    FoodDefinition { kind: FoodKind::Spicy, id: "spicy", weight: 8, score_multiplier: 1 },
+    FoodDefinition { kind: FoodKind::Cosmic, id: "cosmic", weight: 2, score_multiplier: 2 },
+];
+
+impl FoodKind {
+    pub fn definition(self) -> &'static FoodDefinition {
+        FOOD_KINDS
+            .iter()
+            .find(|definition| definition.kind == self)
+            .expect("every food kind has a definition")
+    }
+
+    pub fn from_id(id: &str) -> Option<FoodKind> {
+        FOOD_KINDS.iter().find(|definition| definition.id == id).map(|definition| definition.kind)
+    }
+}
+
+/// Picks the kind of the next food by spawn weight.
+pub fn roll_food_kind(rng: &mut SeededRng) -> FoodKind {
+    let total: u64 = FOOD_KINDS.iter().map(|definition| definition.weight).sum();
+    let mut roll = rng.next_u64() % total;
+    for definition in &FOOD_KINDS {
+        if roll < definition.weight {
+            return definition.kind;
+        }
+        roll -= definition.weight;
+    }
+    FoodKind::Standard
+}
+
+/// Points `food` is worth right now: the run's food value times its kind's multiplier, decayed
+/// with age when the rules make food expire.
+pub fn food_value(food: &Food, food_score_value: u32, config: &GameConfig) -> u32 {
+    let full = food_score_value * food.kind.definition().score_multiplier;
+    (full as f32 * decay_factor(food, config)).round() as u32
+}
+
+/// Share of its full value `food` still has (1.0 when food never expires).
+pub fn decay_factor(food: &Food, config: &GameConfig) -> f32 {
+    match config.food_lifetime_ticks() {
+        Some(lifetime) => config.decay.factor(food.age_ticks as f32 / lifetime as f32),
+        None => 1.0,
+    }
+}
+
+/// Whether `food` has outlived the rules' food lifetime and should move elsewhere.
+pub fn is_expired(food: &Food, config: &GameConfig) -> bool {
+    config.food_lifetime_ticks().is_some_and(|lifetime| food.age_ticks >= lifetime)
+}
+
+/// Food about to expire blinks; this is whether it is in the visible half of a blink.
+pub fn is_visible(food: &Food, config: &GameConfig) -> bool {
+    let Some(lifetime) = config.food_lifetime_ticks() else {
+        return true;
+    };
+    let remaining = lifetime.saturating_sub(food.age_ticks);
+    remaining > FOOD_BLINK_TICKS || (remaining / FOOD_BLINK_PERIOD_TICKS) % 2 == 1
+}
+
+/// Adds `points` for a food of `kind` and changes the worm's length: rotten food takes segments
+/// off the tail (never below the starting length), everything else grows the worm by one.
+pub fn eat(player: &mut Snake, kind: FoodKind, score: &mut u32, points: u32) {
+    *score += points;
+
+    if kind == FoodKind::Rotten {
+        let length = player.body.len().saturating_sub(ROTTEN_SHRINK).max(MIN_SNAKE_LENGTH);
+        player.body.truncate(length);
+        return;
+    }
+
+    // Grow snake by adding a segment
+    if let Some(tail) = player.body.last() {
+        player.body.push(*tail);
+    }
+}
+
+/// Effects that reach beyond the worm's body: spicy food gives a short burst of speed and cosmic
+/// food grants a random perk on the spot.
+pub fn apply_food_effect(state: &mut GameState, kind: FoodKind) {
+    match kind {
+        FoodKind::Spicy => {
+            // Another bite restarts the burst rather than adding to it
+            state.spicy_ticks = SPICY_TICKS;
+            effects::recalculate_stats(state);
+        }
+        FoodKind::Cosmic => {
+            let perk = perks::roll_perk(&mut state.rng);
+            state.powerup_history.push((state.tick_count, perk));
+            perks::apply_powerup_effect(&perk, state);
+        }
+        FoodKind::Standard | FoodKind::Golden | FoodKind::Rotten => {}
+    }
+}
@@ -1,3 +1,6 @@
+pub mod combo;
+pub mod difficulty;
+pub mod effects;
 pub mod perks;
 pub mod tick;
 pub mod background;
@@ -1,110 +1,365 @@
-use std::collections::HashMap;
-use crate::state::constants::physics::{LOWER_BOUND_X, UPPER_BOUND_X, LOWER_BOUND_Y, UPPER_BOUND_Y};
-use crate::state::structs::{Vector2D, LootCrate};
+use crate::state::placement::find_free_position;
+use crate::state::constants::graphics::{ART_HEIGHT, ART_WIDTH};
+use crate::state::constants::timing::{HUNGRY_WORM_TICKS, NEED_FOR_SPEED_TICKS};
+use crate::state::core::effects::{self, Stacking};
+use crate::state::structs::{Direction, Food, GameState, LootCrate, Snake, Stats, Vector2D};
+use crate::state::constants::state::{SWAP_CHARGES_PER_PICK, THIRD_OFFER_CHANCE};
+use crate::state::constants::text::{POWERUP_HUNGRY_WORM, POWERUP_NEED_4_SPEED, POWERUP_REVERSO, POWERUP_SWAP};
+use crate::state::grid::Cell;
+use crate::state::level::Level;
+use crate::state::rng::SeededRng;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Perk {
     NeedForSpeed,
-    HungryWorm
+    HungryWorm,
+    Reverso,
+    Swap,
 }
 
-pub fn get_default_powerups() -> (Perk, Perk) {
-    (Perk::NeedForSpeed, Perk::HungryWorm)
+/// How often a perk turns up in loot crates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
 }
 
-pub fn handle_powerup_selection(
-    powerup_selection_keys: &mut HashMap<String, bool>,
-    highlighted_powerup: &mut Option<usize>,
-    selected_powerup: &mut Option<Perk>,
-    powerup_eligibility: &mut bool,
-    in_powerup_selection: &mut bool,
-) -> bool {
-    let (powerup1, powerup2) = get_default_powerups();
-
-    // Handle A and D keys for powerup navigation
-    if powerup_selection_keys.contains_key("KeyA") {
-        *highlighted_powerup = Some(1);
-        powerup_selection_keys.remove("KeyA");
+impl Rarity {
+    /// Relative draw weight: a common perk is five times as likely as a rare one.
+    pub fn weight(self) -> u64 {
+        match self {
+            Rarity::Common => 10,
+            Rarity::Uncommon => 5,
+            Rarity::Rare => 2,
+        }
+    }
+}
+
+/// Everything the game needs to know about a perk.
+pub struct PerkDefinition {
+    pub perk: Perk,
+    pub id: &'static str, // Stable key used in save files
+    pub name: &'static str,
+    pub description: &'static str,
+    pub sprite_index: usize, // Frame in `sprites.powerups`
+    pub sound: &'static str, // Played by `window.playPowerupSound` when picked
+    pub rarity: Rarity,
+    pub effect: PerkEffect,
+}
+
+/// What picking a perk does.
+pub enum PerkEffect {
+    /// Happens once, the moment the perk is picked.
+    Instant(fn(&mut GameState)),
+    /// Changes the derived stats while active, once per stack. `duration` is in ticks; `None`
+    /// keeps the effect for the rest of the run.
+    Modifier {
+        modify: fn(&mut Stats, u32),
+        duration: Option<u64>,
+        stacking: Stacking,
+    },
+}
+
+/// The perk registry. Adding a perk means adding its variant and one entry here.
+pub const PERKS: [PerkDefinition; 4] = [
+    PerkDefinition {
+        perk: Perk::NeedForSpeed,
+        id: "need_for_speed",
+        name: POWERUP_NEED_4_SPEED.0,
+        description: POWERUP_NEED_4_SPEED.1,
+        sprite_index: 0,
+        sound: "turbo",
+        rarity: Rarity::Common,
+        effect: PerkEffect::Modifier {
+            // Speed boost: each stack reduces the move interval by 25%
+            modify: |stats, stacks| stats.move_interval *= 0.75f32.powi(stacks as i32),
+            duration: Some(NEED_FOR_SPEED_TICKS),
+            stacking: Stacking::Cap(2),
+        },
+    },
+    PerkDefinition {
+        perk: Perk::HungryWorm,
+        id: "hungry_worm",
+        name: POWERUP_HUNGRY_WORM.0,
+        description: POWERUP_HUNGRY_WORM.1,
+        sprite_index: 1,
+        sound: "apple",
+        rarity: Rarity::Common,
+        effect: PerkEffect::Modifier {
+            // Double score: food is worth 2x while active, picking it again buys more time
+            modify: |stats, _| stats.food_score_value *= 2,
+            duration: Some(HUNGRY_WORM_TICKS),
+            stacking: Stacking::Add,
+        },
+    },
+    PerkDefinition {
+        perk: Perk::Reverso,
+        id: "reverso",
+        name: POWERUP_REVERSO.0,
+        description: POWERUP_REVERSO.1,
+        sprite_index: 2,
+        sound: "reverso",
+        rarity: Rarity::Uncommon,
+        effect: PerkEffect::Instant(|state| {
+            // One-time escape: the tail becomes the head
+            reverse_snake(&mut state.player);
+        }),
+    },
+    PerkDefinition {
+        perk: Perk::Swap,
+        id: "swap",
+        name: POWERUP_SWAP.0,
+        description: POWERUP_SWAP.1,
+        sprite_index: 3,
+        sound: "swap",
+        rarity: Rarity::Rare,
+        effect: PerkEffect::Instant(|state| {
+            // Grant charges, spent one at a time with the swap key
+            state.swap_charges += SWAP_CHARGES_PER_PICK;
+        }),
+    },
+];
+
+impl Perk {
+    /// This perk's registry entry.
+    pub fn definition(self) -> &'static PerkDefinition {
+        PERKS
+            .iter()
+            .find(|definition| definition.perk == self)
+            .expect("every perk has a registry entry")
     }
-    if powerup_selection_keys.contains_key("KeyD") {
-        *highlighted_powerup = Some(2);
-        powerup_selection_keys.remove("KeyD");
+
+    /// Display name, as shown on the powerup cards.
+    pub fn name(self) -> &'static str {
+        self.definition().name
     }
 
-    // Handle Space key for powerup selection
-    if powerup_selection_keys.contains_key("Space") {
-        if let Some(powerup_index) = *highlighted_powerup {
-            let chosen_powerup = match powerup_index {
-                1 => powerup1,
-                2 => powerup2,
-                _ => powerup1, // Default fallback
-            };
-            *selected_powerup = Some(chosen_powerup);
-            *powerup_eligibility = false;
-            *in_powerup_selection = false;
-            *highlighted_powerup = None;
-            powerup_selection_keys.clear();
-            return true;
-        }
+    /// Looks a perk up by its registry id.
+    pub fn from_id(id: &str) -> Option<Perk> {
+        PERKS.iter().find(|definition| definition.id == id).map(|definition| definition.perk)
+    }
+}
+
+/// Draws the perks a freshly opened loot crate offers: two, sometimes three, all different.
+/// Each remaining perk is picked with a chance proportional to its rarity weight.
+pub fn roll_powerup_offers(rng: &mut SeededRng) -> Vec<Perk> {
+    let count = if rng.chance(THIRD_OFFER_CHANCE) { 3 } else { 2 };
+    let mut pool: Vec<&PerkDefinition> = PERKS.iter().collect();
+    let mut offers = Vec::with_capacity(count);
+
+    while offers.len() < count && !pool.is_empty() {
+        let index = pick_by_rarity(&pool, rng);
+        offers.push(pool.remove(index).perk);
+    }
+    offers
+}
+
+/// Rolls a single perk with the same rarity weights as crate offers.
+pub fn roll_perk(rng: &mut SeededRng) -> Perk {
+    let pool: Vec<&PerkDefinition> = PERKS.iter().collect();
+    pool[pick_by_rarity(&pool, rng)].perk
+}
+
+fn pick_by_rarity(pool: &[&PerkDefinition], rng: &mut SeededRng) -> usize {
+    let total: u64 = pool.iter().map(|definition| definition.rarity.weight()).sum();
+    let mut roll = rng.next_u64() % total;
+    let mut index = 0;
+    while roll >= pool[index].rarity.weight() {
+        roll -= pool[index].rarity.weight();
+        index += 1;
     }
+    index
+}
 
-    // Handle Escape key for default powerup selection
-    if powerup_selection_keys.contains_key("Escape") {
-        *selected_powerup = Some(powerup1); // Choose first powerup as default
-        *powerup_eligibility = false;
-        *in_powerup_selection = false;
-        *highlighted_powerup = None;
-        powerup_selection_keys.clear();
+pub fn handle_powerup_selection(state: &mut GameState) -> bool {
+    // Nothing to choose from: leave the selection screen empty-handed
+    if state.powerup_offers.is_empty() {
+        state.selected_powerup = None;
+        close_powerup_selection(state);
+        return true;
+    }
+
+    let offer_count = state.powerup_offers.len();
+    let keys = &mut state.powerup_selection_keys;
+
+    // Handle A/D and the arrow keys for powerup navigation (cards are numbered from 1)
+    let highlighted = state.highlighted_powerup.unwrap_or(1);
+    if keys.remove("KeyA").is_some() | keys.remove("ArrowLeft").is_some() {
+        state.highlighted_powerup = Some(highlighted.saturating_sub(1).max(1));
+    }
+    if keys.remove("KeyD").is_some() | keys.remove("ArrowRight").is_some() {
+        state.highlighted_powerup = Some((highlighted + 1).min(offer_count));
+    }
+
+    // Number keys (and clicks on a card, which arrive as number keys) pick a card directly
+    let picked_card = (1..=offer_count).find(|card| {
+        keys.contains_key(&format!("Digit{}", card)) || keys.contains_key(&format!("Numpad{}", card))
+    });
+
+    // Handle Space and Enter keys for powerup selection
+    let chosen_powerup = if let Some(card) = picked_card {
+        state.powerup_offers.get(card - 1).copied()
+    } else if keys.contains_key("Space") || keys.contains_key("Enter") {
+        state
+            .highlighted_powerup
+            .and_then(|powerup_index| state.powerup_offers.get(powerup_index.saturating_sub(1)))
+            .or(state.powerup_offers.first()) // Default fallback
+            .copied()
+    } else if keys.contains_key("Escape") {
+        // Handle Escape key for default powerup selection
+        state.powerup_offers.first().copied()
+    } else {
+        None
+    };
+
+    if let Some(powerup) = chosen_powerup {
+        state.selected_powerup = Some(powerup);
+        state.powerup_history.push((state.tick_count, powerup));
+        close_powerup_selection(state);
         return true;
     }
 
     false
 }
 
-pub fn apply_powerup_effect(powerup: &Perk, move_interval: &mut f32, food_score_value: &mut u32) {
-    match powerup {
-        Perk::NeedForSpeed => {
-            // Speed boost: reduce move interval by 25%
-            *move_interval *= 0.75;
-        }
-        Perk::HungryWorm => {
-            // Double score: increase food score value by 2x
-            *food_score_value *= 2;
+fn close_powerup_selection(state: &mut GameState) {
+    state.powerup_eligibility = false;
+    state.in_powerup_selection = false;
+    state.highlighted_powerup = None;
+    state.powerup_selection_keys.clear();
+}
+
+pub fn apply_powerup_effect(powerup: &Perk, state: &mut GameState) {
+    match powerup.definition().effect {
+        PerkEffect::Instant(apply) => apply(state),
+        PerkEffect::Modifier { .. } => {
+            // Timed and stacking perks work through the active effects list
+            effects::add_effect(&mut state.active_effects, *powerup);
+            effects::recalculate_stats(state);
         }
-        _ => {}
     }
 }
 
-pub fn should_spawn_loot_crate_at_threshold() -> bool {
-    // 20% chance to spawn loot crate
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
-    
-    let mut hasher = DefaultHasher::new();
-    js_sys::Date::now().to_bits().hash(&mut hasher);
-    let hash = hasher.finish();
-    
-    (hash % 100) < 20
+/// Flips the worm in place so the tail becomes the head, heading the way the tail was pointing.
+pub fn reverse_snake(player: &mut Snake) {
+    player.body.reverse();
+    let head = player.body[0];
+
+    // Freshly grown segments overlap the tail, so look for the first one that has moved away
+    let Some(neck) = player.body.iter().skip(1).find(|&&segment| segment != head).copied() else {
+        player.direction = player.direction.opposite();
+        return;
+    };
+
+    let mut dx = head.x - neck.x;
+    let mut dy = head.y - neck.y;
+
+    // A gap wider than half the playfield means the worm wraps around an edge right there
+    if dx.abs() > ART_WIDTH as f32 / 2.0 {
+        dx = -dx;
+    }
+    if dy.abs() > ART_HEIGHT as f32 / 2.0 {
+        dy = -dy;
+    }
+
+    player.direction = if dx.abs() >= dy.abs() {
+        if dx > 0.0 { Direction::Right } else { Direction::Left }
+    } else if dy > 0.0 {
+        Direction::Down
+    } else {
+        Direction::Up
+    };
 }
 
-pub fn spawn_loot_crate(loot_crate: &mut LootCrate) {
-    println!("Spawning loot crate at random position");
-
-    // Generate random position within bounds
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
-    
-    let mut hasher = DefaultHasher::new();
-    js_sys::Date::now().to_bits().hash(&mut hasher);
-    let hash = hasher.finish();
-    
-    let x_range = UPPER_BOUND_X - LOWER_BOUND_X - 32.0; // Account for sprite width
-    let y_range = UPPER_BOUND_Y - LOWER_BOUND_Y - 32.0; // Account for sprite height
-    
-    let x = LOWER_BOUND_X + 16.0 + ((hash % 1000) as f32 / 1000.0) * x_range;
-    let y = LOWER_BOUND_Y + 16.0 + (((hash >> 10) % 1000) as f32 / 1000.0) * y_range;
-    
-    loot_crate.position = Vector2D { x, y };
-    loot_crate.is_active = true;
-    loot_crate.sprite_frame_index = 0;
-    loot_crate.last_sprite_frame_index_update_time = js_sys::Date::now();
-}
\ No newline at end of file
+/// Spends a Swap charge: the whole worm shifts so its head lands on the nearest food, and that
+/// food takes the head's old spot. Returns `false` without using a charge if there is nothing to
+/// swap with, or if the worm would land across a deadly edge, on an obstacle or on itself.
+pub fn trigger_swap(state: &mut GameState) -> bool {
+    let head = state.player.body[0];
+    let distance = |position: Vector2D| (position.x - head.x).powi(2) + (position.y - head.y).powi(2);
+    let nearest = state
+        .food
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| item.is_active)
+        .min_by(|(_, a), (_, b)| distance(a.position).total_cmp(&distance(b.position)))
+        .map(|(index, _)| index);
+    let Some(index) = nearest.filter(|_| state.swap_charges > 0) else {
+        return false;
+    };
+
+    let food = state.food[index].position;
+    let walls = state.walls();
+    let grid_mode = state.config.grid_mode;
+    // Segments only wrap around the edges when they are not walls
+    let moved: Vec<Vector2D> = if grid_mode {
+        // Shift by whole cells so the worm stays on the grid
+        let (from, to) = (Cell::of(head), Cell::of(food));
+        let (cols, rows) = (to.col - from.col, to.row - from.row);
+        state.player.body.iter()
+            .map(|&segment| {
+                let cell = Cell::of(segment);
+                if walls {
+                    Cell { col: cell.col + cols, row: cell.row + rows }.position()
+                } else {
+                    cell.shifted(cols, rows).position()
+                }
+            })
+            .collect()
+    } else {
+        let (dx, dy) = (food.x - head.x, food.y - head.y);
+        state.player.body.iter()
+            .map(|&segment| {
+                if walls {
+                    Vector2D { x: segment.x + dx, y: segment.y + dy }
+                } else {
+                    Vector2D {
+                        x: (segment.x + dx).rem_euclid(ART_WIDTH as f32),
+                        y: (segment.y + dy).rem_euclid(ART_HEIGHT as f32),
+                    }
+                }
+            })
+            .collect()
+    };
+
+    // Never split the worm over a deadly edge or drop it onto an obstacle or its own body
+    let blocked = moved.iter().any(|&segment| {
+        (walls && crate::state::r#loop::is_off_playfield(segment))
+            || crate::state::level::obstacle_at(segment, &state.level.obstacles, grid_mode)
+    });
+    let mut landed = Snake::new(0.0, 0.0, state.player.direction);
+    landed.body = moved;
+    let crosses_itself = if grid_mode {
+        crate::state::grid::check_self_collision(&landed)
+    } else {
+        crate::state::r#loop::check_self_collision(&landed)
+    };
+    if blocked || crosses_itself {
+        return false;
+    }
+
+    state.player.body = landed.body;
+    state.food[index].position = head;
+    state.swap_charges -= 1;
+    true
+}
+
+pub fn spawn_loot_crate(
+    loot_crate: &mut LootCrate,
+    player: &Snake,
+    food: &[Food],
+    level: &Level,
+    grid_mode: bool,
+    current_tick: u64,
+    rng: &mut SeededRng,
+) {
+    // Place the crate on a free spot away from the worm, the food and obstacles
+    let avoid: Vec<Vector2D> = food.iter().filter(|item| item.is_active).map(|item| item.position).collect();
+    if let Some(position) = find_free_position(player, &avoid, &level.crate_spots, &level.obstacles, grid_mode, rng) {
+        loot_crate.position = position;
+        loot_crate.is_active = true;
+        loot_crate.sprite_frame_index = 0;
+        loot_crate.last_sprite_frame_index_update_tick = current_tick;
+    }
+}
@@ -1,173 +1,203 @@
-use crate::state::structs::{Direction, Food, LootCrate, Snake, Vector2D};
-
-pub fn update_game_logic(
-    player: &mut Snake,
-    food: &mut Food,
-    loot_crate: &mut LootCrate,
-    score: &mut u32,
-    food_score_value: u32,
-    powerup_eligibility: &mut bool,
-    in_powerup_selection: &mut bool,
-    highlighted_powerup: &mut Option<usize>,
-    stars_offset_x: &mut usize,
-    stars_sprite_frame_index: &mut usize,
-    stars_last_sprite_frame_update_time: &mut f64,
-    globe_sprite_frame_index: &mut usize,
-    globe_last_sprite_frame_update_time: &mut f64,
-    last_loot_crate_check_time: &mut f64,
-    delta_time: f32,
-) -> Result<bool, wasm_bindgen::JsValue> {
+use crate::state::constants::timing::{GAME_OVER_FRAME_TICKS, LOOT_CRATE_CHECK_TICKS};
+use crate::state::structs::GameState;
+
+/// Runs one tick of normal play. Returns `true` when the run ends, either by crashing or by
+/// filling the board or meeting the level's goal (`state.won`).
+pub fn update_game_logic(state: &mut GameState) -> bool {
+    let current_tick = state.tick_count;
+    state.play_ticks += 1;
+    state.food_eaten = None;
+
     // Update background animation
-    crate::state::r#loop::update_background_animation(
-        stars_offset_x,
-        stars_sprite_frame_index,
-        stars_last_sprite_frame_update_time,
-        globe_sprite_frame_index,
-        globe_last_sprite_frame_update_time,
-        delta_time,
-    );
+    crate::state::r#loop::update_background_animation(&mut state.background, current_tick);
 
     // Update food sprite animation (following original logic)
-    crate::state::r#loop::update_food_sprite_animation(food);
+    for food in state.food.iter_mut() {
+        crate::state::r#loop::update_food_sprite_animation(food, current_tick);
+    }
+
+    // Age the food; items that outlive the rules' lifetime move elsewhere
+    for index in 0..state.food.len() {
+        state.food[index].age_ticks += 1;
+        if state.food[index].is_active && crate::state::core::food::is_expired(&state.food[index], &state.config) {
+            let avoid = crate::state::placement::occupied_positions(&state.food, &state.loot_crate, Some(index));
+            crate::state::placement::respawn_food(
+                &mut state.food[index],
+                &state.player,
+                &avoid,
+                &state.level,
+                state.config.grid_mode,
+                current_tick,
+                &mut state.rng,
+            );
+        }
+    }
+
+    // Count perk effects down and rebuild the stats they modify
+    crate::state::core::effects::update_active_effects(&mut state.active_effects);
+    state.spicy_ticks = state.spicy_ticks.saturating_sub(1);
+    crate::state::core::effects::recalculate_stats(state);
+
+    // A combo ends when its window lapses without a bite
+    state.combo.update();
 
     // Update snake movement
-    crate::state::r#loop::update_snake_movement(player, delta_time);
+    let walls = state.walls();
+    if state.config.grid_mode {
+        crate::state::grid::update_snake_movement(&mut state.player, walls);
+    } else {
+        crate::state::r#loop::update_snake_movement(&mut state.player, walls);
+    }
+
+    // In wall mode, leaving the playfield ends the run
+    if walls && crate::state::r#loop::check_wall_collision(&state.player) {
+        return true; // Game over
+    }
+
+    // Running into an asteroid or station wall ends the run
+    if crate::state::level::check_obstacle_collision(&state.player, &state.level.obstacles, state.config.grid_mode) {
+        return true; // Game over
+    }
 
     // Check for self-collision (snake hitting itself)
-    if crate::state::r#loop::check_self_collision(player) {
-        return Ok(true); // Game over
+    let self_collision = if state.config.grid_mode {
+        crate::state::grid::check_self_collision(&state.player)
+    } else {
+        crate::state::r#loop::check_self_collision(&state.player)
+    };
+    if self_collision {
+        return true; // Game over
     }
 
-    // Check food collision and proximity
-    crate::state::r#loop::check_food_collision(
-        player,
-        food,
-        score,
-        food_score_value,
-    );
-
-    // Check for timed loot crate spawning (25% chance every 10 seconds, only if none active)
-    let current_time = js_sys::Date::now();
-    if current_time - *last_loot_crate_check_time >= 10000.0 && !loot_crate.is_active {
-        *last_loot_crate_check_time = current_time;
-        
-        // 25% chance to spawn loot crate
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-        
-        let mut hasher = DefaultHasher::new();
-        current_time.to_bits().hash(&mut hasher);
-        let hash = hasher.finish();
-        
-        if (hash % 100) < 25 {
-            crate::state::core::perks::spawn_loot_crate(loot_crate);
-            web_sys::console::log_1(&"Loot crate spawned by timer (25% chance)".into());
+    // Check every food item for collision and proximity
+    state.player.food_near = false;
+    for index in 0..state.food.len() {
+        // Bites in quick succession score at the combo multiplier
+        let points = crate::state::core::food::food_value(&state.food[index], state.food_score_value, &state.config)
+            * state.combo.next_multiplier();
+        let eaten = if state.config.grid_mode {
+            crate::state::grid::check_food_collision(
+                &mut state.player,
+                &mut state.food[index],
+                &mut state.score,
+                points,
+            )
         } else {
-            web_sys::console::log_1(&"Loot crate timer triggered but no spawn (75% chance)".into());
+            crate::state::r#loop::check_food_collision(
+                &mut state.player,
+                &mut state.food[index],
+                &mut state.score,
+                points,
+            )
+        };
+        let Some(kind) = eaten else {
+            continue;
+        };
+        state.food_eaten = Some(kind);
+
+        // Rotten food scores nothing, so it breaks the combo rather than extending it
+        if kind == crate::state::core::food::FoodKind::Rotten {
+            state.combo.reset();
+        } else {
+            state.combo.record_bite();
+        }
+
+        // Spicy and cosmic food do more than feed the worm
+        crate::state::core::food::apply_food_effect(state, kind);
+
+        // Respawn eaten food on a free spot. If there is none left the board is full and the run is won
+        let avoid = crate::state::placement::occupied_positions(&state.food, &state.loot_crate, Some(index));
+        if !crate::state::placement::respawn_food(
+            &mut state.food[index],
+            &state.player,
+            &avoid,
+            &state.level,
+            state.config.grid_mode,
+            current_tick,
+            &mut state.rng,
+        ) {
+            state.won = true;
+            return true; // Run is over
+        }
+    }
+
+    // Meeting the level's goal clears it
+    let play_ticks = state.play_ticks;
+    if state.level.goal.is_some_and(|goal| goal.is_met(state.score, state.player.body.len(), play_ticks)) {
+        state.won = true;
+        return true; // Run is over
+    }
+
+    // The difficulty ramp scatters more asteroids as the run goes on
+    crate::state::core::difficulty::spawn_hazards(state);
+
+    // Check for timed loot crate spawning every 10 seconds, only if none active. The odds come
+    // from the difficulty preset and drop as the run ramps up
+    if current_tick - state.last_loot_crate_check_tick >= LOOT_CRATE_CHECK_TICKS && !state.loot_crate.is_active {
+        state.last_loot_crate_check_tick = current_tick;
+
+        if state.rng.chance(crate::state::core::difficulty::crate_chance(state)) {
+            crate::state::core::perks::spawn_loot_crate(
+                &mut state.loot_crate,
+                &state.player,
+                &state.food,
+                &state.level,
+                state.config.grid_mode,
+                current_tick,
+                &mut state.rng,
+            );
         }
     }
 
     // Check loot crate collision (same as food collision)
-    if crate::state::r#loop::check_loot_crate_collision(player, loot_crate, powerup_eligibility, in_powerup_selection, highlighted_powerup) {
-        // Loot crate was eaten, no additional logic needed here
+    let loot_crate_eaten = if state.config.grid_mode {
+        crate::state::grid::check_loot_crate_collision(
+            &state.player,
+            &mut state.loot_crate,
+            &mut state.powerup_eligibility,
+            &mut state.in_powerup_selection,
+            &mut state.highlighted_powerup,
+        )
+    } else {
+        crate::state::r#loop::check_loot_crate_collision(
+            &state.player,
+            &mut state.loot_crate,
+            &mut state.powerup_eligibility,
+            &mut state.in_powerup_selection,
+            &mut state.highlighted_powerup,
+        )
+    };
+    if loot_crate_eaten {
+        // Roll the perks this crate offers
+        state.powerup_offers = crate::state::core::perks::roll_powerup_offers(&mut state.rng);
     }
 
     // Update loot crate sprite animation
-    if loot_crate.is_active {
-        crate::state::r#loop::update_loot_crate_sprite_animation(loot_crate);
+    if state.loot_crate.is_active {
+        crate::state::r#loop::update_loot_crate_sprite_animation(&mut state.loot_crate, current_tick);
     }
 
     // Update head sprite animation (following original logic)
-    crate::state::r#loop::update_head_sprite_animation(player, delta_time);
+    crate::state::r#loop::update_head_sprite_animation(&mut state.player, current_tick);
 
-    Ok(false) // No game over
+    false // No game over
 }
 
-pub fn restart_game(
-    player: &mut Snake,
-    food: &mut Food,
-    loot_crate: &mut LootCrate,
-    score: &mut u32,
-    game_over: &mut bool,
-    last_frame_time: &mut Option<f64>,
-    stars_offset_x: &mut usize,
-    stars_sprite_frame_index: &mut usize,
-    stars_last_sprite_frame_update_time: &mut f64,
-    globe_sprite_frame_index: &mut usize,
-    globe_last_sprite_frame_update_time: &mut f64,
-    game_over_frame: &mut usize,
-    game_over_darkness: &mut f32,
-    game_over_animation_time: &mut f64,
-    last_loot_spawn_score: &mut u32,
-    powerup_eligibility: &mut bool,
-    selected_powerup: &mut Option<crate::state::core::perks::Perk>,
-    food_score_value: &mut u32,
-    in_powerup_selection: &mut bool,
-    highlighted_powerup: &mut Option<usize>,
-    powerup_selection_keys: &mut std::collections::HashMap<String, bool>,
-    last_loot_crate_check_time: &mut f64,
-) {
-    // Reset the game state
-
-    *player = Snake::new(40.0, 150.0, Direction::Right);
-    *food = Food {
-        position: Vector2D { x: 200.0, y: 200.0 },
-        is_active: true,
-        food_sprite_frame_index: 0,
-        food_last_sprite_frame_index_update_time: 0.0,
-    };
-
-    *loot_crate = LootCrate {
-        position: Vector2D { x: 0.0, y: 0.0 },
-        is_active: false,
-        sprite_frame_index: 0,
-        last_sprite_frame_index_update_time: 0.0,
-    };
-
-    *score = 0;
-    *last_loot_spawn_score = 0;
-    *game_over = false;
-    *last_frame_time = None;
-
-    // Reset background animation
-    *stars_offset_x = 0;
-    *stars_sprite_frame_index = 0;
-    *stars_last_sprite_frame_update_time = 0.0;
-    *globe_sprite_frame_index = 0;
-    *globe_last_sprite_frame_update_time = 0.0;
-
-    // Reset game over animation
-    *game_over_frame = 0;
-    *game_over_darkness = 0.5;
-    *game_over_animation_time = 0.0;
-
-    // Reset powerup system
-    *powerup_eligibility = false;
-    *selected_powerup = None;
-    *food_score_value = 100;
-    *in_powerup_selection = false;
-    *highlighted_powerup = None;
-    powerup_selection_keys.clear();
-
-    // Reset loot crate timer
-    *last_loot_crate_check_time = js_sys::Date::now();
+pub fn restart_game(state: &mut GameState) {
+    state.restart_level();
 }
 
-pub fn update_game_over_animation(
-    game_over_frame: &mut usize,
-    game_over_darkness: &mut f32,
-    game_over_animation_time: &mut f64,
-) -> bool {
-    let current_time = js_sys::Date::now();
+pub fn update_game_over_animation(state: &mut GameState) -> bool {
+    let animation = &mut state.game_over_animation;
 
     // Check if enough time has passed for next frame (500ms per frame)
-    if current_time - *game_over_animation_time >= 500.0 {
-        *game_over_frame += 1;
-        *game_over_darkness = (*game_over_darkness + 0.1).min(0.8);
-        *game_over_animation_time = current_time;
+    if state.tick_count - animation.last_frame_update_tick >= GAME_OVER_FRAME_TICKS {
+        animation.frame += 1;
+        animation.darkness = (animation.darkness + 0.1).min(0.8);
+        animation.last_frame_update_tick = state.tick_count;
 
         // After 8 frames, restart the game
-        if *game_over_frame >= 8 {
+        if animation.frame >= 8 {
             return true; // Signal to restart
         }
     }
@@ -0,0 +1,132 @@
+use crate::state::constants::grid::{CELL_HEIGHT, CELL_WIDTH, GRID_COLUMNS, GRID_ROWS};
+use crate::state::core::food::FoodKind;
+use crate::state::structs::{Direction, Food, LootCrate, Snake, Vector2D};
+
+/// A single cell of the grid-mode playfield.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cell {
+    pub col: i32,
+    pub row: i32,
+}
+
+impl Cell {
+    /// The cell whose top-left corner is nearest to `position`.
+    pub fn of(position: Vector2D) -> Self {
+        Cell {
+            col: (position.x / CELL_WIDTH).round() as i32,
+            row: (position.y / CELL_HEIGHT).round() as i32,
+        }
+    }
+
+    pub fn position(self) -> Vector2D {
+        Vector2D {
+            x: self.col as f32 * CELL_WIDTH,
+            y: self.row as f32 * CELL_HEIGHT,
+        }
+    }
+
+    /// The neighbouring cell in `direction`, wrapping around the playfield edges.
+    pub fn step(self, direction: Direction) -> Self {
+        self.neighbour(direction).shifted(0, 0) // Shifting by nothing wraps it back onto the grid
+    }
+
+    /// The neighbouring cell in `direction`, which lies off the grid past an edge.
+    pub fn neighbour(self, direction: Direction) -> Self {
+        match direction {
+            Direction::Right => Cell { col: self.col + 1, row: self.row },
+            Direction::Left => Cell { col: self.col - 1, row: self.row },
+            Direction::Up => Cell { col: self.col, row: self.row - 1 },
+            Direction::Down => Cell { col: self.col, row: self.row + 1 },
+        }
+    }
+
+    /// This cell moved by whole columns and rows, wrapping around the playfield edges.
+    pub fn shifted(self, cols: i32, rows: i32) -> Self {
+        Cell {
+            col: (self.col + cols).rem_euclid(GRID_COLUMNS),
+            row: (self.row + rows).rem_euclid(GRID_ROWS),
+        }
+    }
+}
+
+/// Builds a three-segment snake on consecutive cells, with its head at `head`.
+pub fn spawn_snake(head: Cell, direction: Direction) -> Snake {
+    let mut snake = Snake::new(0.0, 0.0, direction);
+    let behind = direction.opposite();
+
+    let mut cell = head;
+    for segment in snake.body.iter_mut() {
+        *segment = cell.position();
+        cell = cell.step(behind);
+    }
+    snake
+}
+
+pub fn update_snake_movement(player: &mut Snake, walls: bool) {
+    // Update snake movement timer
+    player.move_timer += 1;
+
+    // Move snake one cell when timer reaches interval
+    if player.move_timer >= player.move_interval_ticks() {
+        player.move_timer = 0;
+
+        // With walls the head leaves the grid instead of wrapping; the tick ends the run
+        let head = Cell::of(player.body[0]);
+        let next = if walls { head.neighbour(player.direction) } else { head.step(player.direction) };
+        player.body.pop();
+        player.body.insert(0, next.position());
+    }
+}
+
+pub fn check_self_collision(player: &Snake) -> bool {
+    // The head dies only when it shares a cell with a body segment
+    let head = Cell::of(player.body[0]);
+    player.body[1..].iter().any(|segment| Cell::of(*segment) == head)
+}
+
+pub fn check_food_collision(
+    player: &mut Snake,
+    food: &mut Food,
+    score: &mut u32,
+    points: u32,
+) -> Option<FoodKind> {
+    if !food.is_active {
+        return None;
+    }
+
+    let head = Cell::of(player.body[0]);
+    let food_cell = Cell::of(food.position);
+
+    // Food is near within three cells in either direction; the tick clears this before checking the food items
+    player.food_near |= (head.col - food_cell.col).abs() <= 3 && (head.row - food_cell.row).abs() <= 3;
+
+    if head != food_cell {
+        return None;
+    }
+
+    food.is_active = false;
+    crate::state::core::food::eat(player, food.kind, score, points);
+
+    Some(food.kind) // Food was eaten, the caller respawns it
+}
+
+pub fn check_loot_crate_collision(
+    player: &Snake,
+    loot_crate: &mut LootCrate,
+    powerup_eligibility: &mut bool,
+    in_powerup_selection: &mut bool,
+    highlighted_powerup: &mut Option<usize>,
+) -> bool {
+    if !loot_crate.is_active || Cell::of(player.body[0]) != Cell::of(loot_crate.position) {
+        return false;
+    }
+
+    loot_crate.is_active = false;
+
+    // Trigger powerup selection
+    *powerup_eligibility = true;
+    *in_powerup_selection = true;
+    *highlighted_powerup = Some(1); // Default to first powerup
+
+    true // Loot crate was eaten
+}
@@ -0,0 +1,438 @@
+use std::fmt;
+
+use crate::state::codec::{ByteReader, ByteWriter, DecodeError};
+use crate::state::constants::graphics::{SNAKE_BODY_HEIGHT, SNAKE_BODY_WIDTH};
+use crate::state::constants::grid::{CELL_HEIGHT, CELL_WIDTH, GRID_COLUMNS, GRID_ROWS, HUD_ROWS};
+use crate::state::constants::level::{DEFAULT_SPEED, MAP_ROWS, MAX_SPEED, SPAWN_ROOM};
+use crate::state::constants::physics::COLLISION_TOLERANCE;
+use crate::state::constants::timing::TICKS_PER_SECOND;
+use crate::state::grid::Cell;
+use crate::state::structs::{Direction, Snake, Vector2D};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObstacleKind {
+    Asteroid,
+    StationWall,
+}
+
+/// A solid tile the size of one grid cell. The worm dies when its head runs into one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Obstacle {
+    pub kind: ObstacleKind,
+    pub cell: Cell,
+}
+
+impl Obstacle {
+    pub fn position(&self) -> Vector2D {
+        self.cell.position()
+    }
+
+    /// Whether a `size` (width, height) box at `position` overlaps this tile.
+    pub fn overlaps(&self, position: Vector2D, size: (f32, f32)) -> bool {
+        let tile = self.position();
+        position.x < tile.x + CELL_WIDTH
+            && tile.x < position.x + size.0
+            && position.y < tile.y + CELL_HEIGHT
+            && tile.y < position.y + size.1
+    }
+}
+
+/// What a level asks of the player. Meeting it clears the level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Goal {
+    Score(u32),    // Reach this many points
+    Length(usize), // Grow the worm to this many segments
+    Survive(u64),  // Stay alive this many seconds
+}
+
+impl Goal {
+    /// How far the run is towards the goal, and where it ends: (current, target). Survival counts
+    /// `play_ticks`, so time spent on the selection screen does not count.
+    pub fn progress(&self, score: u32, length: usize, play_ticks: u64) -> (u64, u64) {
+        match *self {
+            Goal::Score(target) => (score as u64, target as u64),
+            Goal::Length(target) => (length as u64, target as u64),
+            Goal::Survive(seconds) => (play_ticks / TICKS_PER_SECOND, seconds),
+        }
+    }
+
+    pub fn is_met(&self, score: u32, length: usize, play_ticks: u64) -> bool {
+        let (current, target) = self.progress(score, length, play_ticks);
+        current >= target
+    }
+
+    /// Short HUD label for the goal's progress, e.g. "LEN 7/20".
+    pub fn label(&self, score: u32, length: usize, play_ticks: u64) -> String {
+        let (current, target) = self.progress(score, length, play_ticks);
+        let name = match self {
+            Goal::Score(_) => "PTS",
+            Goal::Length(_) => "LEN",
+            Goal::Survive(_) => "SEC",
+        };
+        format!("{} {}/{}", name, current.min(target), target)
+    }
+
+    fn parse(value: &str) -> Option<Goal> {
+        let (kind, amount) = value.split_once(char::is_whitespace)?;
+        let amount = amount.trim();
+        match kind.to_ascii_lowercase().as_str() {
+            "score" => amount.parse().ok().map(Goal::Score),
+            "length" => amount.parse().ok().filter(|&length| length > 0).map(Goal::Length),
+            "survive" => amount.parse().ok().map(Goal::Survive),
+            _ => None,
+        }
+    }
+}
+
+/// The layout a run is played on, along with the rules it is meant to be played with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Level {
+    pub name: String,
+    pub wrap: bool,                // Leaving an edge wraps around; off makes the edges deadly
+    pub goal: Option<Goal>,        // Meeting it clears the level
+    pub speed: u32,                // Worm moves per second before perks
+    pub spawn: Cell,               // Where the worm's head starts
+    pub spawn_direction: Direction,
+    pub obstacles: Vec<Obstacle>,
+    pub food_spots: Vec<Cell>,  // Food only appears here when set
+    pub crate_spots: Vec<Cell>, // Loot crates only appear here when set
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Level {
+            name: "Open Space".to_string(),
+            wrap: true,
+            goal: None,
+            speed: DEFAULT_SPEED,
+            spawn: Cell { col: 7, row: 19 },
+            spawn_direction: Direction::Right,
+            obstacles: Vec::new(),
+            food_spots: Vec::new(),
+            crate_spots: Vec::new(),
+        }
+    }
+}
+
+impl Level {
+    /// Seconds between moves at this level's speed.
+    pub fn move_interval(&self) -> f32 {
+        1.0 / self.speed as f32
+    }
+
+    pub fn write(&self, writer: &mut ByteWriter) {
+        writer.str(&self.name);
+        writer.bool(self.wrap);
+        match self.goal {
+            None => writer.u8(0),
+            Some(Goal::Score(points)) => {
+                writer.u8(1);
+                writer.varint(points as u64);
+            }
+            Some(Goal::Length(segments)) => {
+                writer.u8(2);
+                writer.varint(segments as u64);
+            }
+            Some(Goal::Survive(seconds)) => {
+                writer.u8(3);
+                writer.varint(seconds);
+            }
+        }
+        writer.varint(self.speed as u64);
+        write_cell(writer, self.spawn);
+        writer.u8(match self.spawn_direction {
+            Direction::Right => 0,
+            Direction::Left => 1,
+            Direction::Up => 2,
+            Direction::Down => 3,
+        });
+        writer.varint(self.obstacles.len() as u64);
+        for obstacle in &self.obstacles {
+            writer.u8(match obstacle.kind {
+                ObstacleKind::Asteroid => 0,
+                ObstacleKind::StationWall => 1,
+            });
+            write_cell(writer, obstacle.cell);
+        }
+        for spots in [&self.food_spots, &self.crate_spots] {
+            writer.varint(spots.len() as u64);
+            for &cell in spots {
+                write_cell(writer, cell);
+            }
+        }
+    }
+
+    pub fn read(reader: &mut ByteReader) -> Result<Self, DecodeError> {
+        let name = reader.str()?;
+        let wrap = reader.bool()?;
+        let goal = match reader.u8()? {
+            0 => None,
+            1 => Some(Goal::Score(reader.varint()? as u32)),
+            2 => Some(Goal::Length(reader.varint()? as usize)),
+            3 => Some(Goal::Survive(reader.varint()?)),
+            _ => return Err(DecodeError::InvalidValue("level goal")),
+        };
+        let speed = reader.varint()? as u32;
+        if speed == 0 {
+            return Err(DecodeError::InvalidValue("level speed"));
+        }
+        let spawn = read_cell(reader)?;
+        let spawn_direction = match reader.u8()? {
+            0 => Direction::Right,
+            1 => Direction::Left,
+            2 => Direction::Up,
+            3 => Direction::Down,
+            _ => return Err(DecodeError::InvalidValue("spawn direction")),
+        };
+        let count = reader.varint()?;
+        let mut obstacles = Vec::new();
+        for _ in 0..count {
+            let kind = match reader.u8()? {
+                0 => ObstacleKind::Asteroid,
+                1 => ObstacleKind::StationWall,
+                _ => return Err(DecodeError::InvalidValue("obstacle kind")),
+            };
+            obstacles.push(Obstacle { kind, cell: read_cell(reader)? });
+        }
+        let food_spots = read_cells(reader)?;
+        let crate_spots = read_cells(reader)?;
+        Ok(Level {
+            name,
+            wrap,
+            goal,
+            speed,
+            spawn,
+            spawn_direction,
+            obstacles,
+            food_spots,
+            crate_spots,
+        })
+    }
+
+    /// Parses a level written in the text format:
+    ///
+    /// ```text
+    /// name: Asteroid Alley
+    /// wrap: off
+    /// goal: length 20
+    /// speed: 12
+    ///
+    /// ......*...C....
+    /// ..S>.....##....
+    /// ........F......
+    /// ```
+    ///
+    /// The header holds `key: value` lines up to the first blank line; only `name` is required.
+    /// `wrap` is on or off (default on), `goal` is what clears the level (`score N`, `length N`
+    /// or `survive N` seconds, with `target: N` short for a score goal) and `speed` is moves per
+    /// second (default 10). Below it, the map covers the playfield under the HUD,
+    /// one character per cell: `#` station wall, `*` asteroid, `F` fixed food spot, `C` crate
+    /// spawn point, `.` or space for open space. `S` is the worm's head, with an arrow
+    /// (`>` `<` `^` `v`) on the cell it faces. Short rows are padded with open space.
+    pub fn parse(text: &str) -> Result<Level, LevelError> {
+        let mut level = Level::default();
+        let mut name = None;
+        let mut lines = text.lines().enumerate().map(|(index, line)| (index + 1, line));
+
+        // Header
+        let mut header_end = 0;
+        for (line_number, line) in lines.by_ref() {
+            header_end = line_number;
+            if line.trim().is_empty() {
+                break;
+            }
+            let Some((key, value)) = line.split_once(':') else {
+                return Err(LevelError::new(line_number, 1, "expected a `key: value` header line"));
+            };
+            let value_column = key.chars().count() + 2 + (value.len() - value.trim_start().len());
+            let value = value.trim();
+            let error = |message: &str| LevelError::new(line_number, value_column, message);
+            match key.trim().to_ascii_lowercase().as_str() {
+                "name" if value.is_empty() => return Err(error("the name must not be empty")),
+                "name" => name = Some(value.to_string()),
+                "wrap" => {
+                    level.wrap = match value.to_ascii_lowercase().as_str() {
+                        "on" | "yes" | "true" => true,
+                        "off" | "no" | "false" => false,
+                        _ => return Err(error("wrap must be `on` or `off`")),
+                    }
+                }
+                "target" => {
+                    let target = value.parse().map_err(|_| error("target must be a whole number"))?;
+                    level.goal = Some(Goal::Score(target));
+                }
+                "goal" => {
+                    let goal = Goal::parse(value).ok_or_else(|| error("goal must be `score N`, `length N` or `survive N`"))?;
+                    level.goal = Some(goal);
+                }
+                "speed" => {
+                    level.speed = value
+                        .parse()
+                        .ok()
+                        .filter(|speed| (1..=MAX_SPEED).contains(speed))
+                        .ok_or_else(|| error(&format!("speed must be between 1 and {}", MAX_SPEED)))?;
+                }
+                other => {
+                    return Err(LevelError::new(line_number, 1, &format!("unknown header key `{}`", other)));
+                }
+            }
+        }
+        level.name = name.ok_or_else(|| LevelError::new(1, 1, "the header needs a `name`"))?;
+
+        // Map
+        let map_start = header_end + 1;
+        let mut map: Vec<(usize, &str)> = lines.collect();
+        while map.last().is_some_and(|(_, line)| line.trim().is_empty()) {
+            map.pop();
+        }
+        if map.len() > MAP_ROWS as usize {
+            let (line_number, _) = map[MAP_ROWS as usize];
+            return Err(LevelError::new(line_number, 1, &format!("the map has more than {} rows", MAP_ROWS)));
+        }
+
+        let mut spawn = None;
+        let mut arrows = Vec::new();
+        for (row, &(line_number, line)) in map.iter().enumerate() {
+            for (col, glyph) in line.chars().enumerate() {
+                let column = col + 1;
+                if col >= GRID_COLUMNS as usize {
+                    return Err(LevelError::new(
+                        line_number,
+                        column,
+                        &format!("the map is wider than {} columns", GRID_COLUMNS),
+                    ));
+                }
+                let cell = Cell { col: col as i32, row: row as i32 + HUD_ROWS };
+                match glyph {
+                    '.' | ' ' => {}
+                    '#' => level.obstacles.push(Obstacle { kind: ObstacleKind::StationWall, cell }),
+                    '*' => level.obstacles.push(Obstacle { kind: ObstacleKind::Asteroid, cell }),
+                    'F' => level.food_spots.push(cell),
+                    'C' => level.crate_spots.push(cell),
+                    'S' if spawn.is_some() => {
+                        return Err(LevelError::new(line_number, column, "the map has more than one spawn point"));
+                    }
+                    'S' => spawn = Some((cell, line_number, column)),
+                    '>' => arrows.push((cell, Direction::Right, line_number, column)),
+                    '<' => arrows.push((cell, Direction::Left, line_number, column)),
+                    '^' => arrows.push((cell, Direction::Up, line_number, column)),
+                    'v' => arrows.push((cell, Direction::Down, line_number, column)),
+                    other => {
+                        return Err(LevelError::new(line_number, column, &format!("unknown map symbol `{}`", other)));
+                    }
+                }
+            }
+        }
+
+        // The spawn point faces the one arrow next to it
+        let Some((spawn, spawn_line, spawn_column)) = spawn else {
+            return Err(LevelError::new(map_start, 1, "the map has no spawn point `S`"));
+        };
+        let mut direction = None;
+        for (cell, arrow, line_number, column) in arrows {
+            if spawn.neighbour(arrow) != cell {
+                return Err(LevelError::new(line_number, column, "arrows must point away from the spawn point next to them"));
+            }
+            if direction.is_some() {
+                return Err(LevelError::new(line_number, column, "the spawn point has more than one arrow"));
+            }
+            direction = Some(arrow);
+        }
+        let Some(direction) = direction else {
+            return Err(LevelError::new(spawn_line, spawn_column, "the spawn point needs an arrow next to it"));
+        };
+
+        // The rest of the worm trails behind its head and must fit there
+        let mut cell = spawn;
+        for _ in 0..SPAWN_ROOM {
+            cell = cell.neighbour(direction.opposite());
+            let on_map = (0..GRID_COLUMNS).contains(&cell.col) && (HUD_ROWS..GRID_ROWS).contains(&cell.row);
+            if !on_map || level.obstacles.iter().any(|obstacle| obstacle.cell == cell) {
+                return Err(LevelError::new(spawn_line, spawn_column, "there is no room for the worm behind the spawn point"));
+            }
+        }
+
+        level.spawn = spawn;
+        level.spawn_direction = direction;
+        Ok(level)
+    }
+}
+
+fn write_cell(writer: &mut ByteWriter, cell: Cell) {
+    writer.signed_varint(cell.col as i64);
+    writer.signed_varint(cell.row as i64);
+}
+
+fn read_cell(reader: &mut ByteReader) -> Result<Cell, DecodeError> {
+    let col = i32::try_from(reader.signed_varint()?).map_err(|_| DecodeError::InvalidValue("cell"))?;
+    let row = i32::try_from(reader.signed_varint()?).map_err(|_| DecodeError::InvalidValue("cell"))?;
+    Ok(Cell { col, row })
+}
+
+fn read_cells(reader: &mut ByteReader) -> Result<Vec<Cell>, DecodeError> {
+    let count = reader.varint()?;
+    let mut cells = Vec::new();
+    for _ in 0..count {
+        cells.push(read_cell(reader)?);
+    }
+    Ok(cells)
+}
+
+/// Why a level text could not be parsed, pointing at the offending line and column (both
+/// counted from 1).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LevelError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl LevelError {
+    fn new(line: usize, column: usize, message: &str) -> Self {
+        LevelError { line, column, message: message.to_string() }
+    }
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// The built-in levels, written in the level text format.
+pub const LEVELS: [&str; 3] = [
+    include_str!("../../assets/levels/open_space.txt"),
+    include_str!("../../assets/levels/asteroid_belt.txt"),
+    include_str!("../../assets/levels/space_station.txt"),
+];
+
+/// The built-in level at `index`, if there is one.
+pub fn builtin_level(index: usize) -> Option<Level> {
+    LEVELS
+        .get(index)
+        .map(|text| Level::parse(text).expect("built-in levels are valid"))
+}
+
+/// Whether the worm's head has run into an obstacle. Grid mode compares cells; free mode
+/// overlaps the head with each tile, forgiving a pixel of contact.
+pub fn check_obstacle_collision(player: &Snake, obstacles: &[Obstacle], grid_mode: bool) -> bool {
+    obstacle_at(player.body[0], obstacles, grid_mode)
+}
+
+/// Whether a worm segment at `position` runs into an obstacle, by the same rules as the head.
+pub fn obstacle_at(position: Vector2D, obstacles: &[Obstacle], grid_mode: bool) -> bool {
+    if grid_mode {
+        let cell = Cell::of(position);
+        return obstacles.iter().any(|obstacle| obstacle.cell == cell);
+    }
+
+    let inset = Vector2D {
+        x: position.x + COLLISION_TOLERANCE,
+        y: position.y + COLLISION_TOLERANCE,
+    };
+    let size = (
+        SNAKE_BODY_WIDTH - 2.0 * COLLISION_TOLERANCE,
+        SNAKE_BODY_HEIGHT - 2.0 * COLLISION_TOLERANCE,
+    );
+    obstacles.iter().any(|obstacle| obstacle.overlaps(inset, size))
+}
@@ -1,15 +1,20 @@
-use crate::state::structs::{Snake, Food, LootCrate, Direction};
-use crate::state::constants::graphics::{SNAKE_BODY_WIDTH, SNAKE_BODY_HEIGHT};
+use crate::state::core::food::FoodKind;
+use crate::state::structs::{BackgroundAnimation, Snake, Food, LootCrate, Direction, Vector2D};
+use crate::state::constants::graphics::{ART_HEIGHT, ART_WIDTH, SNAKE_BODY_WIDTH, SNAKE_BODY_HEIGHT};
+use crate::state::constants::placement::HUD_HEIGHT;
 use crate::state::constants::physics::{LOWER_BOUND_X, LOWER_BOUND_Y, UPPER_BOUND_X, UPPER_BOUND_Y};
-use crate::state::constants::graphics::{ART_WIDTH, ART_HEIGHT};
+use crate::state::constants::timing::{
+    FOOD_FRAME_TICKS, GLOBE_FRAME_TICKS, HEAD_FRAME_TICKS, LOOT_CRATE_FRAME_TICKS,
+    STARS_FRAME_TICKS, STARS_SCROLL_TICKS,
+};
 
-pub fn update_snake_movement(player: &mut Snake, delta_time: f32) {
+pub fn update_snake_movement(player: &mut Snake, walls: bool) {
     // Update snake movement timer
-    player.move_timer += delta_time;
-    
-    // Move snake when timer exceeds interval
-    if player.move_timer >= player.move_interval {
-        player.move_timer = 0.0;
+    player.move_timer += 1;
+
+    // Move snake when timer reaches interval
+    if player.move_timer >= player.move_interval_ticks() {
+        player.move_timer = 0;
         
         // Move body segments first (from tail to neck)
         let body_size = player.body.len();
@@ -30,6 +35,11 @@ pub fn update_snake_movement(player: &mut Snake, delta_time: f32) {
                 Direction::Down => head.y += 8.0,
             }
             
+            // With walls the head leaves the playfield instead; the tick ends the run
+            if walls {
+                return;
+            }
+            
             // Handle bounds wrapping like the original
             // Wrap horizontally
             if head.x < LOWER_BOUND_X {
@@ -66,7 +76,25 @@ pub fn check_self_collision(player: &Snake) -> bool {
     false
 }
 
-pub fn check_food_collision(player: &mut Snake, food: &mut Food, score: &mut u32, food_score_value: u32) -> bool {
+/// Whether the head has left the walled playfield: the whole screen below the HUD strip.
+pub fn check_wall_collision(player: &Snake) -> bool {
+    is_off_playfield(player.body[0])
+}
+
+/// Whether a worm segment at `position` sticks out of the playfield.
+pub fn is_off_playfield(position: Vector2D) -> bool {
+    position.x < 0.0
+        || position.x + SNAKE_BODY_WIDTH > ART_WIDTH as f32
+        || position.y < HUD_HEIGHT
+        || position.y + SNAKE_BODY_HEIGHT > ART_HEIGHT as f32
+}
+
+pub fn check_food_collision(
+    player: &mut Snake,
+    food: &mut Food,
+    score: &mut u32,
+    points: u32,
+) -> Option<FoodKind> {
     // Check food collision and proximity
     if food.is_active {
         if let Some(head) = player.body.first() {
@@ -76,107 +104,62 @@ pub fn check_food_collision(player: &mut Snake, food: &mut Food, score: &mut u32
             
             // Check if food is near (within 24 pixels like the original)
             if distance < 24.0 {
-                player.food_near = true;
+                player.food_near = true; // The tick clears this before checking the food items
                 
                 // Check if food is eaten (within 12 pixels like the original)
                 if distance < 12.0 {
                     food.is_active = false;
-                    *score += food_score_value;
-                    
-                    // Grow snake by adding a segment
-                    if let Some(tail) = player.body.last() {
-                        player.body.push(*tail);
-                    }
-                    
-                    // Respawn food at random location
-                    food.position.x = (js_sys::Math::random() * (ART_WIDTH as f64 - 60.0)) as f32;
-                    food.position.y = (js_sys::Math::random() * (ART_HEIGHT as f64 - 60.0)) as f32;
-                    food.is_active = true;
-                    food.food_sprite_frame_index = 0;
-                    food.food_last_sprite_frame_index_update_time = js_sys::Date::now();
+                    crate::state::core::food::eat(player, food.kind, score, points);
                     
-                    return true; // Food was eaten
+                    return Some(food.kind); // Food was eaten, the caller respawns it
                 }
-            } else {
-                player.food_near = false;
             }
         }
     }
-    false
+    None
 }
 
-pub fn update_background_animation(
-    stars_offset_x: &mut usize,
-    stars_sprite_frame_index: &mut usize,
-    stars_last_sprite_frame_update_time: &mut f64,
-    globe_sprite_frame_index: &mut usize,
-    globe_last_sprite_frame_update_time: &mut f64,
-    _delta_time: f32,
-) {
-    let current_time = js_sys::Date::now();
-    
+pub fn update_background_animation(background: &mut BackgroundAnimation, current_tick: u64) {
     // Update stars animation frame (blinking effect every 250ms)
-    if current_time - *stars_last_sprite_frame_update_time >= 250.0 {
-        *stars_sprite_frame_index = (*stars_sprite_frame_index + 1) % 6;
-        *stars_last_sprite_frame_update_time = current_time;
+    if current_tick - background.stars_last_sprite_frame_update_tick >= STARS_FRAME_TICKS {
+        background.stars_sprite_frame_index = (background.stars_sprite_frame_index + 1) % 6;
+        background.stars_last_sprite_frame_update_tick = current_tick;
     }
-    
+
     // Update globe animation frame (rotation effect every 1000ms)
-    if current_time - *globe_last_sprite_frame_update_time >= 1000.0 {
-        *globe_sprite_frame_index = (*globe_sprite_frame_index + 1) % 6;
-        *globe_last_sprite_frame_update_time = current_time;
+    if current_tick - background.globe_last_sprite_frame_update_tick >= GLOBE_FRAME_TICKS {
+        background.globe_sprite_frame_index = (background.globe_sprite_frame_index + 1) % 6;
+        background.globe_last_sprite_frame_update_tick = current_tick;
     }
-    
+
     // Update parallax offset (continuous scrolling)
-    *stars_offset_x = stars_offset_x.wrapping_add(1);
+    if current_tick.is_multiple_of(STARS_SCROLL_TICKS) {
+        background.stars_offset_x = background.stars_offset_x.wrapping_add(1);
+    }
 }
 
-pub fn update_head_sprite_animation(player: &mut Snake, _delta_time: f32) {
+pub fn update_head_sprite_animation(player: &mut Snake, current_tick: u64) {
     if player.food_near {
         // When food is near, use sprite frame 3
         player.head_sprite_frame_index = 3;
     } else {
         // Simple animation: alternate between frames 0 and 1 every 500ms
-        let current_time = js_sys::Date::now();
-        let cycle_position = (current_time as u64) % 1000; // 1 second cycle
-        
-        player.head_sprite_frame_index = if cycle_position < 500 { 0 } else { 1 };
+        let cycle_position = current_tick % (HEAD_FRAME_TICKS * 2); // 1 second cycle
+
+        player.head_sprite_frame_index = if cycle_position < HEAD_FRAME_TICKS { 0 } else { 1 };
     }
 }
 
-pub fn update_food_sprite_animation(food: &mut Food) {
+pub fn update_food_sprite_animation(food: &mut Food, current_tick: u64) {
     if !food.is_active {
         return; // No need to update if food is not active
     }
 
-    let current_time = js_sys::Date::now();
-
     // Update food animation frame (toggle every 500ms)
-    if current_time - food.food_last_sprite_frame_index_update_time >= 500.0 {
+    if current_tick - food.food_last_sprite_frame_index_update_tick >= FOOD_FRAME_TICKS {
         food.food_sprite_frame_index = (food.food_sprite_frame_index + 1) % 2;
-        food.food_last_sprite_frame_index_update_time = current_time;
-    }
-}
-
-pub fn update_game_over_animation(
-    game_over_frame: &mut usize,
-    game_over_darkness: &mut f32,
-    game_over_animation_time: &mut f64,
-) -> bool {
-    let current_time = js_sys::Date::now();
-    
-    // Check if enough time has passed for next frame (600ms per frame)
-    if current_time - *game_over_animation_time >= 600.0 {
-        *game_over_frame += 1;
-        *game_over_darkness = (*game_over_darkness + 0.1).min(0.8);
-        *game_over_animation_time = current_time;
-        
-        // After 8 frames, restart the game
-        if *game_over_frame >= 8 {
-            return true; // Signal to restart
-        }
+        food.food_last_sprite_frame_index_update_tick = current_tick;
     }
-    false
 }
 
 pub fn check_loot_crate_collision(
@@ -209,12 +192,10 @@ pub fn check_loot_crate_collision(
     false
 }
 
-pub fn update_loot_crate_sprite_animation(loot_crate: &mut LootCrate) {
-    let current_time = js_sys::Date::now();
-
+pub fn update_loot_crate_sprite_animation(loot_crate: &mut LootCrate, current_tick: u64) {
     // Update loot crate animation frame (toggle every 750 for glowing effect)
-    if current_time - loot_crate.last_sprite_frame_index_update_time >= 750.0 {
+    if current_tick - loot_crate.last_sprite_frame_index_update_tick >= LOOT_CRATE_FRAME_TICKS {
         loot_crate.sprite_frame_index = (loot_crate.sprite_frame_index + 1) % 2;
-        loot_crate.last_sprite_frame_index_update_time = current_time;
+        loot_crate.last_sprite_frame_index_update_tick = current_tick;
     }
 }
\ No newline at end of file
@@ -3,5 +3,13 @@ pub mod structs;
 pub mod platform_types;
 pub mod r#loop;
 pub mod core;
-
-
+pub mod rng;
+pub mod simulation;
+pub mod codec;
+pub mod replay;
+pub mod save;
+pub mod config;
+pub mod grid;
+pub mod placement;
+pub mod level;
+pub mod campaign;
@@ -0,0 +1,122 @@
+use crate::state::constants::graphics::{ART_HEIGHT, ART_WIDTH};
+use crate::state::constants::grid::{GRID_COLUMNS, GRID_ROWS};
+use crate::state::constants::placement::{EAT_RADIUS, HEAD_CLEARANCE, HUD_HEIGHT, ITEM_SIZE};
+use crate::state::core::food::roll_food_kind;
+use crate::state::grid::Cell;
+use crate::state::level::{Level, Obstacle};
+use crate::state::rng::SeededRng;
+use crate::state::structs::{Food, LootCrate, Snake, Vector2D};
+
+fn distance(a: Vector2D, b: Vector2D) -> f32 {
+    let dx = a.x - b.x;
+    let dy = a.y - b.y;
+    (dx * dx + dy * dy).sqrt()
+}
+
+/// Candidate spots for food and loot crates: the level's fixed `spots`, or every cell of the grid
+/// when it has none, keeping those whose item sprite fits fully on screen below the HUD.
+fn candidate_positions(spots: &[Cell]) -> Vec<Vector2D> {
+    let cells: Vec<Cell> = if spots.is_empty() {
+        (0..GRID_ROWS)
+            .flat_map(|row| (0..GRID_COLUMNS).map(move |col| Cell { col, row }))
+            .collect()
+    } else {
+        spots.to_vec()
+    };
+    cells
+        .into_iter()
+        .map(Cell::position)
+        .filter(|position| {
+            position.y >= HUD_HEIGHT
+                && position.x + ITEM_SIZE <= ART_WIDTH as f32
+                && position.y + ITEM_SIZE <= ART_HEIGHT as f32
+        })
+        .collect()
+}
+
+/// Whether an item at `position` would sit on the worm. Grid mode uses exact cells; free mode
+/// uses the same radius as eating, so nothing spawns already inside the worm's mouth.
+fn covered_by_snake(position: Vector2D, player: &Snake, grid_mode: bool) -> bool {
+    if grid_mode {
+        let cell = Cell::of(position);
+        player.body.iter().any(|segment| Cell::of(*segment) == cell)
+    } else {
+        player.body.iter().any(|segment| distance(*segment, position) < EAT_RADIUS)
+    }
+}
+
+/// Picks a random free spot for a food item or loot crate, out of `spots` if the level fixes
+/// where the item may appear.
+///
+/// A spot is free when it is below the HUD, not on the worm, not touching an obstacle and not on
+/// top of any position in `avoid` (other items on the board). Spots at least `HEAD_CLEARANCE`
+/// away from the head are preferred; if none remain, any free spot is used. Returns `None` when
+/// the board is full.
+pub fn find_free_position(
+    player: &Snake,
+    avoid: &[Vector2D],
+    spots: &[Cell],
+    obstacles: &[Obstacle],
+    grid_mode: bool,
+    rng: &mut SeededRng,
+) -> Option<Vector2D> {
+    let free: Vec<Vector2D> = candidate_positions(spots)
+        .into_iter()
+        .filter(|&position| !covered_by_snake(position, player, grid_mode))
+        .filter(|&position| !obstacles.iter().any(|obstacle| obstacle.overlaps(position, (ITEM_SIZE, ITEM_SIZE))))
+        .filter(|&position| avoid.iter().all(|&other| distance(other, position) >= ITEM_SIZE))
+        .collect();
+
+    let head = player.body[0];
+    let clear: Vec<Vector2D> = free
+        .iter()
+        .copied()
+        .filter(|&position| distance(head, position) >= HEAD_CLEARANCE)
+        .collect();
+
+    let pool = if clear.is_empty() { free } else { clear };
+    if pool.is_empty() {
+        return None;
+    }
+    Some(pool[(rng.next_u64() % pool.len() as u64) as usize])
+}
+
+/// Positions of the items on the board that new items keep clear of: every active food item
+/// except the one at `except` (the item being moved), and the loot crate.
+pub fn occupied_positions(food: &[Food], loot_crate: &LootCrate, except: Option<usize>) -> Vec<Vector2D> {
+    food.iter()
+        .enumerate()
+        .filter(|&(index, item)| item.is_active && Some(index) != except)
+        .map(|(_, item)| item.position)
+        .chain(loot_crate.is_active.then_some(loot_crate.position))
+        .collect()
+}
+
+/// Moves eaten food to a free spot, away from the worm, the items at `avoid` and the level's
+/// obstacles, and rolls what kind it will be. Returns `false` and leaves the food inactive when
+/// the board is full.
+pub fn respawn_food(
+    food: &mut Food,
+    player: &Snake,
+    avoid: &[Vector2D],
+    level: &Level,
+    grid_mode: bool,
+    current_tick: u64,
+    rng: &mut SeededRng,
+) -> bool {
+    food.food_sprite_frame_index = 0;
+    food.food_last_sprite_frame_index_update_tick = current_tick;
+    match find_free_position(player, avoid, &level.food_spots, &level.obstacles, grid_mode, rng) {
+        Some(position) => {
+            food.position = position;
+            food.kind = roll_food_kind(rng);
+            food.is_active = true;
+            food.age_ticks = 0;
+            true
+        }
+        None => {
+            food.is_active = false;
+            false
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use crate::state::codec::{ByteReader, ByteWriter, DecodeError};
+use crate::state::config::GameConfig;
+use crate::state::level::Level;
+use crate::state::save::{read_option, write_option};
+
+const REPLAY_MAGIC: &[u8; 4] = b"SWRP";
+// Bump whenever the layout below changes; older replays are rejected rather than misread
+pub const REPLAY_VERSION: u8 = 1;
+
+/// A single `handle_key_down` call, tagged with the number of ticks the run had completed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayInput {
+    pub tick: u64,
+    pub key_code: String,
+}
+
+/// Everything needed to reproduce a run exactly: the seed, rules, level and campaign stage plus
+/// every key press in order.
+///
+/// `ticks` and `score` describe where the recording stopped (game over, or the moment it was
+/// exported), so a verifier can compare the claimed score against a fresh simulation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replay {
+    pub seed: u64,
+    pub config: GameConfig,
+    pub level: Level,
+    pub campaign_stage: Option<usize>, // Campaign stages ignore the difficulty preset
+    pub ticks: u64,
+    pub score: u32,
+    pub inputs: Vec<ReplayInput>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, config: GameConfig) -> Self {
+        Replay::with_level(seed, config, Level::default(), None)
+    }
+
+    pub fn with_level(seed: u64, config: GameConfig, level: Level, campaign_stage: Option<usize>) -> Self {
+        Replay { seed, config, level, campaign_stage, ticks: 0, score: 0, inputs: Vec::new() }
+    }
+
+    pub fn record(&mut self, tick: u64, key_code: &str) {
+        self.inputs.push(ReplayInput { tick, key_code: key_code.to_string() });
+    }
+
+    /// Encodes the replay as a compact binary blob. Input ticks are stored as deltas.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = ByteWriter::new();
+        writer.header(REPLAY_MAGIC, REPLAY_VERSION);
+        writer.u64(self.seed);
+        self.config.write(&mut writer);
+        self.level.write(&mut writer);
+        write_option(&mut writer, self.campaign_stage, |w, stage| w.varint(stage as u64));
+        writer.varint(self.ticks);
+        writer.u32(self.score);
+        writer.varint(self.inputs.len() as u64);
+
+        let mut previous_tick = 0;
+        for input in &self.inputs {
+            writer.varint(input.tick - previous_tick);
+            writer.str(&input.key_code);
+            previous_tick = input.tick;
+        }
+        writer.into_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut reader = ByteReader::new(bytes);
+        reader.header(REPLAY_MAGIC, REPLAY_VERSION)?;
+        let seed = reader.u64()?;
+        let config = GameConfig::read(&mut reader)?;
+        let level = Level::read(&mut reader)?;
+        let campaign_stage = read_option(&mut reader, |r| Ok(r.varint()? as usize))?;
+        let ticks = reader.varint()?;
+        let score = reader.u32()?;
+        let input_count = reader.varint()?;
+
+        let mut inputs = Vec::new();
+        let mut tick = 0u64;
+        for _ in 0..input_count {
+            tick = tick.checked_add(reader.varint()?).ok_or(DecodeError::InvalidValue("input tick"))?;
+            let key_code = reader.str()?;
+            inputs.push(ReplayInput { tick, key_code });
+        }
+
+        Ok(Replay { seed, config, level, campaign_stage, ticks, score, inputs })
+    }
+}
+
+/// Feeds a recorded replay back into the simulation, tick by tick.
+pub struct ReplayPlayer {
+    replay: Replay,
+    next_input: usize,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        ReplayPlayer { replay, next_input: 0 }
+    }
+
+    /// Returns the key presses that were recorded once `tick` ticks of the run had completed.
+    pub fn inputs_due(&mut self, tick: u64) -> Vec<String> {
+        let mut due = Vec::new();
+        while let Some(input) = self.replay.inputs.get(self.next_input) {
+            if input.tick > tick {
+                break;
+            }
+            due.push(input.key_code.clone());
+            self.next_input += 1;
+        }
+        due
+    }
+
+    /// True once the run has reached the point where the recording stopped.
+    pub fn is_finished(&self, tick: u64) -> bool {
+        tick >= self.replay.ticks
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+}
@@ -0,0 +1,42 @@
+/// Small seeded PRNG (SplitMix64) that drives every random roll in the simulation.
+///
+/// The whole generator is a single `u64`, so it can be stored alongside the rest of the game
+/// state and restored later to continue the exact same sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        SeededRng { state: seed }
+    }
+
+    /// Restores a generator from a value previously returned by `state()`.
+    pub fn from_state(state: u64) -> Self {
+        SeededRng { state }
+    }
+
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniformly distributed value in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        // Use the top 53 bits for a uniformly distributed mantissa
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// Returns `true` with the given probability in percent (0-100).
+    pub fn chance(&mut self, percent: u32) -> bool {
+        (self.next_u64() % 100) < percent as u64
+    }
+}
@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+
+use crate::state::codec::{ByteReader, ByteWriter, DecodeError};
+use crate::state::config::GameConfig;
+use crate::state::level::Level;
+use crate::state::core::combo::Combo;
+use crate::state::core::effects::ActiveEffect;
+use crate::state::core::food::FoodKind;
+use crate::state::core::perks::Perk;
+use crate::state::replay::Replay;
+use crate::state::rng::SeededRng;
+use crate::state::structs::{
+    BackgroundAnimation, Direction, Food, GameOverAnimation, GameState, LootCrate, Snake, Stats, Vector2D,
+};
+
+const SAVE_MAGIC: &[u8; 4] = b"SWSV";
+// Bump whenever the layout below changes; older saves are rejected rather than misread
+pub const SAVE_VERSION: u8 = 1;
+
+/// Serializes a run in progress, together with its input recording so a resumed run can still
+/// be exported as a verifiable replay.
+pub fn encode(state: &GameState, recording: &Replay) -> Vec<u8> {
+    let mut writer = ByteWriter::new();
+    writer.header(SAVE_MAGIC, SAVE_VERSION);
+
+    writer.u64(state.seed);
+    state.config.write(&mut writer);
+    state.level.write(&mut writer);
+    write_option(&mut writer, state.campaign_stage, |w, stage| w.varint(stage as u64));
+    writer.varint(state.hazards as u64);
+    writer.u64(state.rng.state());
+    writer.u64(state.tick_count);
+    writer.u64(state.run_start_tick);
+    writer.varint(state.play_ticks);
+
+    write_snake(&mut writer, &state.player);
+    writer.varint(state.food.len() as u64);
+    for food in &state.food {
+        write_food(&mut writer, food);
+    }
+    write_loot_crate(&mut writer, &state.loot_crate);
+    write_background(&mut writer, &state.background);
+
+    writer.bool(state.game_over);
+    writer.bool(state.won);
+    writer.varint(state.game_over_animation.frame as u64);
+    writer.f32(state.game_over_animation.darkness);
+    writer.u64(state.game_over_animation.last_frame_update_tick);
+
+    writer.u32(state.score);
+    writer.u32(state.food_score_value);
+    writer.varint(state.combo.streak as u64);
+    writer.varint(state.combo.remaining_ticks);
+    writer.varint(state.combo.best as u64);
+    writer.u32(state.last_loot_spawn_score);
+    writer.u64(state.last_loot_crate_check_tick);
+
+    // Powerup system
+    writer.bool(state.powerup_eligibility);
+    writer.bool(state.in_powerup_selection);
+    write_option(&mut writer, state.highlighted_powerup, |w, index| w.varint(index as u64));
+    let mut keys: Vec<_> = state.powerup_selection_keys.iter().collect();
+    keys.sort();
+    writer.varint(keys.len() as u64);
+    for (key, pressed) in keys {
+        writer.str(key);
+        writer.bool(*pressed);
+    }
+    writer.varint(state.powerup_offers.len() as u64);
+    for &perk in &state.powerup_offers {
+        writer.str(perk.definition().id);
+    }
+    write_option(&mut writer, state.selected_powerup, |w, perk| w.str(perk.definition().id));
+    writer.varint(state.powerup_history.len() as u64);
+    for &(tick, perk) in &state.powerup_history {
+        writer.u64(tick);
+        writer.str(perk.definition().id);
+    }
+    writer.u32(state.swap_charges);
+    writer.f32(state.base_stats.move_interval);
+    writer.u32(state.base_stats.food_score_value);
+    writer.varint(state.active_effects.len() as u64);
+    for effect in &state.active_effects {
+        writer.str(effect.perk.definition().id);
+        writer.varint(effect.stacks as u64);
+        write_option(&mut writer, effect.remaining_ticks, |w, ticks| w.varint(ticks));
+    }
+    writer.varint(state.spicy_ticks);
+
+    writer.bytes(&recording.to_bytes());
+    writer.into_bytes()
+}
+
+/// Restores a run written by `encode`.
+pub fn decode(bytes: &[u8]) -> Result<(GameState, Replay), DecodeError> {
+    let mut reader = ByteReader::new(bytes);
+    reader.header(SAVE_MAGIC, SAVE_VERSION)?;
+
+    let seed = reader.u64()?;
+    let config = GameConfig::read(&mut reader)?;
+    let mut state = GameState::with_level(seed, config, Level::read(&mut reader)?);
+    state.campaign_stage = read_option(&mut reader, |r| Ok(r.varint()? as usize))?;
+    state.hazards = reader.varint()? as usize;
+    if state.hazards > state.level.obstacles.len() {
+        return Err(DecodeError::InvalidValue("hazard count"));
+    }
+    state.rng = SeededRng::from_state(reader.u64()?);
+    state.tick_count = reader.u64()?;
+    state.run_start_tick = reader.u64()?;
+    state.play_ticks = reader.varint()?;
+
+    state.player = read_snake(&mut reader)?;
+    let food_count = reader.varint()?;
+    state.food = Vec::new();
+    for _ in 0..food_count {
+        state.food.push(read_food(&mut reader)?);
+    }
+    state.loot_crate = read_loot_crate(&mut reader)?;
+    state.background = read_background(&mut reader)?;
+
+    state.game_over = reader.bool()?;
+    state.won = reader.bool()?;
+    state.game_over_animation = GameOverAnimation {
+        frame: reader.varint()? as usize,
+        darkness: reader.f32()?,
+        last_frame_update_tick: reader.u64()?,
+    };
+
+    state.score = reader.u32()?;
+    state.food_score_value = reader.u32()?;
+    state.combo = Combo {
+        streak: reader.varint()? as u32,
+        remaining_ticks: reader.varint()?,
+        best: reader.varint()? as u32,
+    };
+    state.last_loot_spawn_score = reader.u32()?;
+    state.last_loot_crate_check_tick = reader.u64()?;
+
+    // Powerup system
+    state.powerup_eligibility = reader.bool()?;
+    state.in_powerup_selection = reader.bool()?;
+    state.highlighted_powerup = read_option(&mut reader, |r| Ok(r.varint()? as usize))?;
+    let key_count = reader.varint()?;
+    let mut keys = HashMap::new();
+    for _ in 0..key_count {
+        let key = reader.str()?;
+        keys.insert(key, reader.bool()?);
+    }
+    state.powerup_selection_keys = keys;
+    let offer_count = reader.varint()?;
+    state.powerup_offers = Vec::new();
+    for _ in 0..offer_count {
+        state.powerup_offers.push(read_perk(&mut reader)?);
+    }
+    state.selected_powerup = read_option(&mut reader, read_perk)?;
+    let history_len = reader.varint()?;
+    for _ in 0..history_len {
+        let tick = reader.u64()?;
+        state.powerup_history.push((tick, read_perk(&mut reader)?));
+    }
+    state.swap_charges = reader.u32()?;
+    state.base_stats = Stats {
+        move_interval: reader.f32()?,
+        food_score_value: reader.u32()?,
+    };
+    let effect_count = reader.varint()?;
+    for _ in 0..effect_count {
+        state.active_effects.push(ActiveEffect {
+            perk: read_perk(&mut reader)?,
+            stacks: reader.varint()? as u32,
+            remaining_ticks: read_option(&mut reader, |r| r.varint())?,
+        });
+    }
+    state.spicy_ticks = reader.varint()?;
+
+    let recording = Replay::from_bytes(reader.bytes()?)?;
+    if !reader.is_empty() {
+        return Err(DecodeError::InvalidValue("trailing data"));
+    }
+
+    Ok((state, recording))
+}
+
+fn write_vector(writer: &mut ByteWriter, vector: Vector2D) {
+    writer.f32(vector.x);
+    writer.f32(vector.y);
+}
+
+fn read_vector(reader: &mut ByteReader) -> Result<Vector2D, DecodeError> {
+    Ok(Vector2D { x: reader.f32()?, y: reader.f32()? })
+}
+
+pub(crate) fn write_option<T>(writer: &mut ByteWriter, value: Option<T>, write: impl FnOnce(&mut ByteWriter, T)) {
+    writer.bool(value.is_some());
+    if let Some(value) = value {
+        write(writer, value);
+    }
+}
+
+pub(crate) fn read_option<'a, T>(
+    reader: &mut ByteReader<'a>,
+    read: impl FnOnce(&mut ByteReader<'a>) -> Result<T, DecodeError>,
+) -> Result<Option<T>, DecodeError> {
+    if reader.bool()? {
+        read(reader).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+fn write_snake(writer: &mut ByteWriter, snake: &Snake) {
+    writer.u8(match snake.direction {
+        Direction::Right => 0,
+        Direction::Left => 1,
+        Direction::Up => 2,
+        Direction::Down => 3,
+    });
+    writer.varint(snake.body.len() as u64);
+    for segment in &snake.body {
+        write_vector(writer, *segment);
+    }
+    writer.u32(snake.move_timer);
+    writer.f32(snake.move_interval);
+    writer.varint(snake.body_sprite_frame_index as u64);
+    writer.u64(snake.body_last_sprite_frame_index_update_tick);
+    writer.varint(snake.head_sprite_frame_index as u64);
+    writer.u64(snake.head_last_sprite_frame_index_update_tick);
+    writer.bool(snake.food_near);
+}
+
+fn read_snake(reader: &mut ByteReader) -> Result<Snake, DecodeError> {
+    let direction = match reader.u8()? {
+        0 => Direction::Right,
+        1 => Direction::Left,
+        2 => Direction::Up,
+        3 => Direction::Down,
+        _ => return Err(DecodeError::InvalidValue("direction")),
+    };
+    let body_len = reader.varint()?;
+    let mut body = Vec::new();
+    for _ in 0..body_len {
+        body.push(read_vector(reader)?);
+    }
+    if body.is_empty() {
+        return Err(DecodeError::InvalidValue("snake body"));
+    }
+
+    Ok(Snake {
+        direction,
+        body,
+        move_timer: reader.u32()?,
+        move_interval: reader.f32()?,
+        body_sprite_frame_index: reader.varint()? as usize,
+        body_last_sprite_frame_index_update_tick: reader.u64()?,
+        head_sprite_frame_index: reader.varint()? as usize,
+        head_last_sprite_frame_index_update_tick: reader.u64()?,
+        food_near: reader.bool()?,
+    })
+}
+
+fn write_food(writer: &mut ByteWriter, food: &Food) {
+    write_vector(writer, food.position);
+    writer.str(food.kind.definition().id);
+    writer.bool(food.is_active);
+    writer.varint(food.age_ticks);
+    writer.varint(food.food_sprite_frame_index as u64);
+    writer.u64(food.food_last_sprite_frame_index_update_tick);
+}
+
+fn read_food(reader: &mut ByteReader) -> Result<Food, DecodeError> {
+    Ok(Food {
+        position: read_vector(reader)?,
+        kind: FoodKind::from_id(&reader.str()?).ok_or(DecodeError::InvalidValue("food kind"))?,
+        is_active: reader.bool()?,
+        age_ticks: reader.varint()?,
+        food_sprite_frame_index: reader.varint()? as usize,
+        food_last_sprite_frame_index_update_tick: reader.u64()?,
+    })
+}
+
+fn write_loot_crate(writer: &mut ByteWriter, loot_crate: &LootCrate) {
+    write_vector(writer, loot_crate.position);
+    writer.bool(loot_crate.is_active);
+    writer.varint(loot_crate.sprite_frame_index as u64);
+    writer.u64(loot_crate.last_sprite_frame_index_update_tick);
+}
+
+fn read_loot_crate(reader: &mut ByteReader) -> Result<LootCrate, DecodeError> {
+    Ok(LootCrate {
+        position: read_vector(reader)?,
+        is_active: reader.bool()?,
+        sprite_frame_index: reader.varint()? as usize,
+        last_sprite_frame_index_update_tick: reader.u64()?,
+    })
+}
+
+fn write_background(writer: &mut ByteWriter, background: &BackgroundAnimation) {
+    writer.varint(background.stars_offset_x as u64);
+    writer.varint(background.stars_sprite_frame_index as u64);
+    writer.u64(background.stars_last_sprite_frame_update_tick);
+    writer.varint(background.globe_sprite_frame_index as u64);
+    writer.u64(background.globe_last_sprite_frame_update_tick);
+}
+
+fn read_background(reader: &mut ByteReader) -> Result<BackgroundAnimation, DecodeError> {
+    Ok(BackgroundAnimation {
+        stars_offset_x: reader.varint()? as usize,
+        stars_sprite_frame_index: reader.varint()? as usize,
+        stars_last_sprite_frame_update_tick: reader.u64()?,
+        globe_sprite_frame_index: reader.varint()? as usize,
+        globe_last_sprite_frame_update_tick: reader.u64()?,
+    })
+}
+
+/// Perks are stored by registry id, so reordering or adding perks keeps old saves readable.
+fn read_perk(reader: &mut ByteReader) -> Result<Perk, DecodeError> {
+    Perk::from_id(&reader.str()?).ok_or(DecodeError::InvalidValue("perk"))
+}
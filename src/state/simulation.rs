@@ -0,0 +1,469 @@
+use std::collections::HashMap;
+
+use crate::platform::{ManualClock, PlatformClock};
+use crate::state::constants::timing::{MAX_FRAME_TIME_MS, TICK_DURATION_MS};
+use crate::state::core::difficulty::Difficulty;
+use crate::state::core::food::FoodKind;
+use crate::state::core::perks::{self, Perk};
+use crate::state::campaign;
+use crate::state::codec::DecodeError;
+use crate::state::config::GameConfig;
+use crate::state::core::tick;
+use crate::state::level::Level;
+use crate::state::replay::{Replay, ReplayPlayer};
+use crate::state::rng::SeededRng;
+use crate::state::save;
+use crate::state::structs::GameState;
+
+/// Things that happened during a simulation step which the front end may want to react to
+/// (sound effects, music, logging). The simulation itself never touches the platform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimulationEvent {
+    FoodEaten(FoodKind),
+    PowerupSelectionStarted,
+    PowerupSelectionEnded(Option<Perk>),
+    SwapTriggered,
+    GameOver,
+    Won,
+    StageCleared(usize), // Campaign stage index; the next stage is unlocked
+    NewHighScore(Difficulty), // The run that just ended beat the preset's best score
+    Restarted,
+    StateLoaded,
+    Paused,
+    Resumed,
+}
+
+/// Result of playing a replay back headlessly, for offline score verification.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayOutcome {
+    pub score: u32,
+    pub length: usize,
+    pub ticks: u64,
+    pub perks: Vec<(u64, Perk)>, // (run tick, perk) in the order they were picked
+    pub game_over: bool,
+    pub won: bool,
+}
+
+/// Platform-free Space Worm game. Time is injected, so the same code drives the browser build
+/// (`WebClock`) and headless native runs (`ManualClock`). All randomness comes from the seeded
+/// generator in `GameState`, so a given seed always produces the same food and loot crate sequence.
+///
+/// Game logic runs in fixed ticks (`TICKS_PER_SECOND`). Wall-clock time is only used to decide
+/// how many ticks to run per frame; every timer inside the game is measured in ticks.
+pub struct Simulation<C: PlatformClock> {
+    pub clock: C,
+    pub state: GameState,
+    pub last_frame_time: Option<f64>,
+    accumulator_ms: f64,
+    paused: bool,
+    events: Vec<SimulationEvent>,
+    // Input log of the run in progress, and of the last run that ended in game over
+    recording: Replay,
+    last_replay: Option<Replay>,
+    playback: Option<ReplayPlayer>,
+    // Casual play draws a new seed for every run; seeded games repeat theirs
+    fresh_seed_each_run: bool,
+    // Highest campaign stage the player may start
+    campaign_unlocked: usize,
+    // Best score on each difficulty preset
+    high_scores: HashMap<Difficulty, u32>,
+}
+
+impl<C: PlatformClock> Simulation<C> {
+    pub fn new(clock: C, seed: u64) -> Self {
+        Simulation::with_config(clock, seed, GameConfig::default())
+    }
+
+    pub fn with_config(clock: C, seed: u64, config: GameConfig) -> Self {
+        Simulation {
+            clock,
+            state: GameState::with_config(seed, config),
+            last_frame_time: None,
+            accumulator_ms: 0.0,
+            paused: false,
+            events: Vec::new(),
+            recording: Replay::new(seed, config),
+            last_replay: None,
+            playback: None,
+            fresh_seed_each_run: false,
+            campaign_unlocked: 0,
+            high_scores: HashMap::new(),
+        }
+    }
+
+    /// Advances the game by one rendered frame, using the injected clock to measure elapsed time.
+    pub fn step(&mut self) {
+        let current_time = self.clock.now_ms();
+        let elapsed_ms = match self.last_frame_time {
+            Some(last_time) => current_time - last_time,
+            None => 0.0,
+        };
+        self.last_frame_time = Some(current_time);
+
+        self.advance(elapsed_ms);
+    }
+
+    /// Adds `elapsed_ms` of wall time to the accumulator and runs as many fixed ticks as fit.
+    pub fn advance(&mut self, elapsed_ms: f64) {
+        // Time spent paused is dropped rather than caught up on resume
+        if self.paused {
+            return;
+        }
+
+        self.accumulator_ms += elapsed_ms.clamp(0.0, MAX_FRAME_TIME_MS);
+        while self.accumulator_ms >= TICK_DURATION_MS {
+            self.accumulator_ms -= TICK_DURATION_MS;
+            self.tick();
+        }
+    }
+
+    /// Runs exactly one fixed simulation tick.
+    pub fn tick(&mut self) {
+        if self.paused {
+            return;
+        }
+
+        self.feed_replay_inputs();
+        self.state.tick_count += 1;
+
+        if self.state.game_over {
+            if tick::update_game_over_animation(&mut self.state) {
+                self.finish_run();
+            }
+            return;
+        }
+
+        // Simulation is frozen while the player picks a powerup
+        if self.state.in_powerup_selection {
+            self.handle_powerup_selection();
+            return;
+        }
+
+        self.update_game_logic();
+    }
+
+    /// How far the accumulator is into the next tick (0.0..1.0), for render interpolation.
+    pub fn interpolation_alpha(&self) -> f32 {
+        (self.accumulator_ms / TICK_DURATION_MS) as f32
+    }
+
+    pub fn handle_key_down(&mut self, key_code: &str) {
+        // Pausing is outside the recorded input, so replays can be paused too
+        if crate::input::handler::handle_pause_input(key_code) && self.can_pause() {
+            self.toggle_pause();
+            return;
+        }
+        if self.paused {
+            return;
+        }
+
+        // Live input is ignored while a replay is driving the game, except to skip its game over
+        if self.playback.is_some() && !self.state.game_over {
+            return;
+        }
+        self.apply_key(key_code);
+    }
+
+    fn apply_key(&mut self, key_code: &str) {
+        if self.state.game_over {
+            // Allow restarting the game with Space key
+            if crate::input::handler::handle_game_over_input(key_code) {
+                self.finish_run();
+            }
+            return;
+        }
+
+        self.recording.record(self.state.run_ticks(), key_code);
+
+        if crate::input::handler::handle_swap_input(key_code) && !self.state.in_powerup_selection {
+            if perks::trigger_swap(&mut self.state) {
+                self.events.push(SimulationEvent::SwapTriggered);
+            }
+            return;
+        }
+
+        crate::input::handler::handle_key_down(
+            key_code,
+            &mut self.state.player.direction,
+            self.state.game_over,
+            self.state.in_powerup_selection,
+            &mut self.state.powerup_selection_keys,
+        );
+    }
+
+    /// Only normal play can be paused; powerup selection already freezes the game.
+    fn can_pause(&self) -> bool {
+        !self.state.game_over && !self.state.in_powerup_selection
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn pause(&mut self) {
+        if !self.paused && self.can_pause() {
+            self.paused = true;
+            self.accumulator_ms = 0.0;
+            self.events.push(SimulationEvent::Paused);
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if self.paused {
+            self.paused = false;
+            self.events.push(SimulationEvent::Resumed);
+        }
+    }
+
+    pub fn toggle_pause(&mut self) {
+        if self.paused {
+            self.resume();
+        } else {
+            self.pause();
+        }
+    }
+
+    /// Starts a new run, on a new seed if `set_fresh_seed_each_run` is on.
+    pub fn restart(&mut self) {
+        if self.fresh_seed_each_run {
+            self.state.seed = SeededRng::new(self.state.seed).next_u64();
+        }
+        self.begin_run();
+    }
+
+    /// Makes every later run draw a new seed, derived from the previous one, instead of playing
+    /// the same sequence again. Each run's seed still goes into its replay.
+    pub fn set_fresh_seed_each_run(&mut self, fresh: bool) {
+        self.fresh_seed_each_run = fresh;
+    }
+
+    fn begin_run(&mut self) {
+        tick::restart_game(&mut self.state);
+        self.recording = Replay::with_level(
+            self.state.seed,
+            self.state.config,
+            self.state.level.clone(),
+            self.state.campaign_stage,
+        );
+        self.playback = None;
+        self.paused = false;
+        self.events.push(SimulationEvent::Restarted);
+    }
+
+    /// Restarts from the replay's seed and plays its recorded inputs back on their original ticks.
+    pub fn load_replay(&mut self, replay: Replay) {
+        self.state.seed = replay.seed;
+        self.state.config = replay.config;
+        self.state.set_level(replay.level.clone());
+        self.state.campaign_stage = replay.campaign_stage;
+        self.begin_run();
+        self.playback = Some(ReplayPlayer::new(replay));
+    }
+
+    /// Starts a new run under different rules.
+    pub fn set_config(&mut self, config: GameConfig) {
+        self.state.config = config;
+        self.restart();
+    }
+
+    /// Starts a new run on `level`, keeping the current rules. This leaves the campaign.
+    pub fn load_level(&mut self, level: Level) {
+        self.state.set_level(level);
+        self.state.campaign_stage = None;
+        self.restart();
+    }
+
+    /// Starts campaign stage `stage`. Returns `false` if it does not exist or is still locked.
+    pub fn start_campaign(&mut self, stage: usize) -> bool {
+        if stage > self.campaign_unlocked {
+            return false;
+        }
+        let Some(level) = campaign::stage(stage) else {
+            return false;
+        };
+        self.state.set_level(level);
+        self.state.campaign_stage = Some(stage);
+        self.restart();
+        true
+    }
+
+    pub fn campaign_unlocked(&self) -> usize {
+        self.campaign_unlocked
+    }
+
+    /// Restores campaign progress persisted by the front end.
+    pub fn set_campaign_unlocked(&mut self, stage: usize) {
+        self.campaign_unlocked = stage.min(campaign::STAGES.len() - 1);
+    }
+
+    /// Best score of any run on `difficulty`, 0 if none has ended yet.
+    pub fn high_score(&self, difficulty: Difficulty) -> u32 {
+        self.high_scores.get(&difficulty).copied().unwrap_or(0)
+    }
+
+    /// Restores a high score persisted by the front end.
+    pub fn set_high_score(&mut self, difficulty: Difficulty, score: u32) {
+        self.high_scores.insert(difficulty, score);
+    }
+
+    /// Keeps the score of the run that just ended if it beats its preset's best. Replays are
+    /// left out; they only repeat a run that already counted.
+    fn record_high_score(&mut self) {
+        if self.playback.is_some() {
+            return;
+        }
+        let difficulty = self.state.config.difficulty;
+        let best = self.high_scores.entry(difficulty).or_insert(0);
+        if self.state.score > *best {
+            *best = self.state.score;
+            self.events.push(SimulationEvent::NewHighScore(difficulty));
+        }
+    }
+
+    /// Leaves the game over or stage clear screen: a cleared campaign stage moves on to the next
+    /// one (or back to open space after the last), anything else restarts the level.
+    fn finish_run(&mut self) {
+        let Some(stage) = self.state.campaign_stage.filter(|_| self.state.won) else {
+            self.restart();
+            return;
+        };
+        match campaign::stage(stage + 1) {
+            Some(level) => {
+                self.state.set_level(level);
+                self.state.campaign_stage = Some(stage + 1);
+                self.restart();
+            }
+            None => self.load_level(Level::default()),
+        }
+    }
+
+    /// Serializes the run in progress, including its input recording.
+    pub fn save_state(&self) -> Vec<u8> {
+        save::encode(&self.state, &self.recording)
+    }
+
+    /// Resumes a run written by `save_state`. The current game is left untouched on error.
+    pub fn load_state(&mut self, bytes: &[u8]) -> Result<(), DecodeError> {
+        let (state, recording) = save::decode(bytes)?;
+        self.state = state;
+        self.recording = recording;
+        self.playback = None;
+        self.paused = false;
+        self.accumulator_ms = 0.0;
+        self.events.push(SimulationEvent::StateLoaded);
+        Ok(())
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.playback.is_some()
+    }
+
+    /// The last run that ended in game over, or the run in progress if none has finished yet.
+    pub fn export_replay(&self) -> Replay {
+        match &self.last_replay {
+            Some(replay) => replay.clone(),
+            None => self.current_replay(),
+        }
+    }
+
+    /// Snapshot of the input log for the run in progress.
+    pub fn current_replay(&self) -> Replay {
+        let mut replay = self.recording.clone();
+        replay.ticks = self.state.run_ticks();
+        replay.score = self.state.score;
+        replay
+    }
+
+    fn feed_replay_inputs(&mut self) {
+        let Some(playback) = &mut self.playback else {
+            return;
+        };
+
+        // Hand control back to the player once the recording runs out
+        let run_ticks = self.state.run_ticks();
+        if playback.is_finished(run_ticks) {
+            self.playback = None;
+            return;
+        }
+
+        for key_code in playback.inputs_due(run_ticks) {
+            self.apply_key(&key_code);
+        }
+    }
+
+    /// Takes all events produced since the last call.
+    pub fn drain_events(&mut self) -> Vec<SimulationEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn update_game_logic(&mut self) {
+        // Store previous values to detect state changes
+        let previous_in_powerup_selection = self.state.in_powerup_selection;
+
+        let game_over = tick::update_game_logic(&mut self.state);
+
+        if let Some(kind) = self.state.food_eaten {
+            self.events.push(SimulationEvent::FoodEaten(kind));
+        }
+
+        // Check if powerup selection just started
+        if !previous_in_powerup_selection && self.state.in_powerup_selection {
+            self.events.push(SimulationEvent::PowerupSelectionStarted);
+        }
+
+        if game_over {
+            self.state.game_over = true;
+            self.state.game_over_animation.last_frame_update_tick = self.state.tick_count;
+            self.last_replay = Some(self.current_replay());
+            let event = match (self.state.won, self.state.campaign_stage) {
+                (true, Some(stage)) => {
+                    // Watching a replay of a stage doesn't unlock anything
+                    if self.playback.is_none() {
+                        self.campaign_unlocked = self.campaign_unlocked.max(campaign::unlocked_after(stage));
+                    }
+                    SimulationEvent::StageCleared(stage)
+                }
+                (true, None) => SimulationEvent::Won,
+                (false, _) => SimulationEvent::GameOver,
+            };
+            self.events.push(event);
+            self.record_high_score();
+        }
+    }
+
+    fn handle_powerup_selection(&mut self) {
+        if perks::handle_powerup_selection(&mut self.state) {
+            // A powerup was selected, apply its effect
+            if let Some(powerup) = self.state.selected_powerup {
+                perks::apply_powerup_effect(&powerup, &mut self.state);
+            }
+            self.events.push(SimulationEvent::PowerupSelectionEnded(self.state.selected_powerup));
+        }
+    }
+}
+
+impl Simulation<ManualClock> {
+    /// Plays `replay` back on a fresh simulation until game over or the end of the recording.
+    pub fn run_replay(replay: &Replay) -> ReplayOutcome {
+        let mut sim = Simulation::with_config(ManualClock::new(0.0), replay.seed, replay.config);
+        sim.load_replay(replay.clone());
+        while !sim.state.game_over && sim.state.run_ticks() < replay.ticks {
+            sim.tick();
+        }
+
+        let run_start_tick = sim.state.run_start_tick;
+        ReplayOutcome {
+            score: sim.state.score,
+            length: sim.state.player.body.len(),
+            ticks: sim.state.run_ticks(),
+            perks: sim
+                .state
+                .powerup_history
+                .iter()
+                .map(|&(tick, perk)| (tick - run_start_tick, perk))
+                .collect(),
+            game_over: sim.state.game_over,
+            won: sim.state.won,
+        }
+    }
+}
@@ -1,37 +1,19 @@
+use crate::state::config::GameConfig;
 use crate::state::constants::graphics::{SNAKE_BODY_HEIGHT, SNAKE_BODY_WIDTH};
+use crate::state::constants::state::{BASE_FOOD_SCORE_VALUE, BASE_MOVE_INTERVAL};
+use crate::state::constants::timing::TICKS_PER_SECOND;
+use crate::state::core::combo::Combo;
+use crate::state::core::effects::ActiveEffect;
+use crate::state::core::food::FoodKind;
+use crate::state::core::perks::Perk;
+use crate::state::constants::placement::ITEM_SIZE;
+use crate::state::grid::{self, Cell};
+use crate::state::level::Level;
+use crate::state::placement;
+use crate::state::rng::SeededRng;
+use std::collections::HashMap;
 
-#[derive(Debug, Clone, Copy)]
-pub struct PlatformInstant(f64);
-
-#[derive(Debug, Clone, Copy)]
-pub struct PlatformDuration(f64);
-
-impl PlatformInstant {
-    pub fn now() -> Self {
-        PlatformInstant(js_sys::Date::now())
-    }
-
-    pub fn duration_since(&self, earlier: PlatformInstant) -> PlatformDuration {
-        PlatformDuration(self.0 - earlier.0)
-    }
-
-    pub fn elapsed(&self) -> PlatformDuration {
-        PlatformDuration(js_sys::Date::now() - self.0)
-    }
-}
-
-impl PlatformDuration {
-    pub fn as_secs_f32(&self) -> f32 {
-        self.0 as f32 / 1000.0 // Convert milliseconds to seconds
-    }
-    
-    pub fn as_millis(&self) -> u64 {
-        self.0 as u64 // Value is already in milliseconds
-    }
-}
-
-
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Vector2D {
     pub x: f32,
     pub y: f32,
@@ -45,15 +27,26 @@ pub enum Direction {
     Down,
 }
 
+impl Direction {
+    pub fn opposite(self) -> Self {
+        match self {
+            Direction::Right => Direction::Left,
+            Direction::Left => Direction::Right,
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+        }
+    }
+}
+
 pub struct Snake {
     pub direction: Direction,
     pub body: Vec<Vector2D>,
-    pub move_timer: f32,
-    pub move_interval: f32,
+    pub move_timer: u32, // Ticks since the last move
+    pub move_interval: f32, // Seconds between moves
     pub body_sprite_frame_index: usize,
-    pub body_last_sprite_frame_index_update_time: f64,
+    pub body_last_sprite_frame_index_update_tick: u64,
     pub head_sprite_frame_index: usize,
-    pub head_last_sprite_frame_index_update_time: f64,
+    pub head_last_sprite_frame_index_update_tick: u64,
     pub food_near: bool,
 }
 
@@ -86,94 +79,279 @@ impl Snake {
         Snake {
             direction: initial_direction,
             body,
-            move_timer: 0.0,
-            move_interval: 0.1, // Default is 10 moves per second
+            move_timer: 0,
+            move_interval: BASE_MOVE_INTERVAL, // Default is 10 moves per second
             body_sprite_frame_index: 0,
-            body_last_sprite_frame_index_update_time: 0.0,
+            body_last_sprite_frame_index_update_tick: 0,
             head_sprite_frame_index: 0,
-            head_last_sprite_frame_index_update_time: 0.0,
+            head_last_sprite_frame_index_update_tick: 0,
             food_near: false,
         }
     }
+
+    /// Number of simulation ticks between moves for the current `move_interval`.
+    pub fn move_interval_ticks(&self) -> u32 {
+        ((self.move_interval * TICKS_PER_SECOND as f32).round() as u32).max(1)
+    }
 }
 
 pub struct Food {
     pub position: Vector2D,
+    pub kind: FoodKind,
     pub is_active: bool,
+    pub age_ticks: u64, // Ticks of play since it appeared, for expiry and value decay
     pub food_sprite_frame_index: usize,
-    pub food_last_sprite_frame_index_update_time: f64,
+    pub food_last_sprite_frame_index_update_tick: u64,
+}
+
+impl Food {
+    /// A standard food item at `position`, at the start of its animation.
+    pub fn new(position: Vector2D) -> Self {
+        Food {
+            position,
+            kind: FoodKind::Standard,
+            is_active: true,
+            age_ticks: 0,
+            food_sprite_frame_index: 0,
+            food_last_sprite_frame_index_update_tick: 0,
+        }
+    }
 }
 
 pub struct LootCrate {
     pub position: Vector2D,
     pub is_active: bool,
     pub sprite_frame_index: usize,
-    pub last_sprite_frame_index_update_time: f64,
+    pub last_sprite_frame_index_update_tick: u64,
 }
 
 
+pub struct BackgroundAnimation {
+    pub stars_offset_x: usize,
+    pub stars_sprite_frame_index: usize,
+    pub stars_last_sprite_frame_update_tick: u64,
+    pub globe_sprite_frame_index: usize,
+    pub globe_last_sprite_frame_update_tick: u64,
+}
+
+pub struct GameOverAnimation {
+    pub frame: usize,
+    pub darkness: f32,
+    pub last_frame_update_tick: u64,
+}
+
+/// The values perks modify. `GameState::base_stats` holds them with no perk applied; the live
+/// values on the worm and the state are recalculated from it every tick.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub move_interval: f32,
+    pub food_score_value: u32,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Stats {
+            move_interval: BASE_MOVE_INTERVAL,
+            food_score_value: BASE_FOOD_SCORE_VALUE,
+        }
+    }
+}
+
+/// The complete model of a run. Everything the simulation reads or writes lives here, so game
+/// logic takes a single `&mut GameState` instead of a list of loose fields.
 pub struct GameState {
+    pub seed: u64,
+    pub config: GameConfig,
+    pub level: Level,
+    pub campaign_stage: Option<usize>, // Set while the level is a campaign stage
+    pub hazards: usize, // Asteroids the difficulty ramp added to the end of the level's obstacles
+    pub rng: SeededRng,
+    pub tick_count: u64,
+    pub run_start_tick: u64, // tick_count when the current run began
+    pub play_ticks: u64, // Ticks of normal play this run, leaving out frozen screens
     pub player: Snake,
-    pub food: Food,
+    pub food: Vec<Food>, // Every food item on the board
     pub loot_crate: LootCrate,
-    pub delta_time: f32,
-    pub last_frame_time: Option<PlatformInstant>,
+    pub background: BackgroundAnimation,
     pub game_over: bool,
+    pub won: bool, // The run ended because the worm filled the board
+    pub game_over_animation: GameOverAnimation,
     pub score: u32,
     pub food_score_value: u32,
+    pub food_eaten: Option<FoodKind>, // What the worm ate this tick, for events
+    pub combo: Combo,
     pub last_loot_spawn_score: u32,
+    pub last_loot_crate_check_tick: u64,
+    // Powerup system
     pub powerup_eligibility: bool,
-    pub selected_powerup: Option<crate::state::core::perks::Perk>,
-    pub powerup_history: std::collections::HashMap<u32, crate::state::core::perks::Perk>,
+    pub in_powerup_selection: bool,
+    pub highlighted_powerup: Option<usize>,
+    pub powerup_selection_keys: HashMap<String, bool>,
+    pub powerup_offers: Vec<Perk>, // The perks on the selection screen, rolled when a crate is eaten
+    pub selected_powerup: Option<Perk>,
+    pub powerup_history: Vec<(u64, Perk)>, // (tick, perk) for every perk picked this run
+    pub swap_charges: u32, // Remaining uses of the Swap perk
+    pub base_stats: Stats,
+    pub active_effects: Vec<ActiveEffect>, // Perks still modifying the stats
+    pub spicy_ticks: u64, // Time left of the speed burst from spicy food
 }
 
 impl GameState {
-    pub fn new(player: Snake) -> Self {
-        GameState {
-            player,
-            food: Food {
-                position: Vector2D { x: 100.0, y: 100.0 },
-                is_active: false,
-                food_sprite_frame_index: 0,
-                food_last_sprite_frame_index_update_time: 0.0,
-            },
+    pub fn new(seed: u64) -> Self {
+        GameState::with_config(seed, GameConfig::default())
+    }
+
+    pub fn with_config(seed: u64, config: GameConfig) -> Self {
+        GameState::with_level(seed, config, Level::default())
+    }
+
+    pub fn with_level(seed: u64, config: GameConfig, level: Level) -> Self {
+        let spawn = level.spawn.position();
+        let spawn_direction = level.spawn_direction;
+        let base_stats = Stats { move_interval: level.move_interval(), ..Stats::default() };
+        let mut state = GameState {
+            seed,
+            config,
+            level,
+            campaign_stage: None,
+            hazards: 0,
+            rng: SeededRng::new(seed),
+            tick_count: 0,
+            run_start_tick: 0,
+            play_ticks: 0,
+            player: Snake::new(spawn.x, spawn.y, spawn_direction),
+            food: vec![Food::new(Vector2D { x: 200.0, y: 200.0 })],
             loot_crate: LootCrate {
                 position: Vector2D { x: 0.0, y: 0.0 },
                 is_active: false,
                 sprite_frame_index: 0,
-                last_sprite_frame_index_update_time: 0.0,
+                last_sprite_frame_index_update_tick: 0,
+            },
+            background: BackgroundAnimation {
+                stars_offset_x: 0,
+                stars_sprite_frame_index: 0,
+                stars_last_sprite_frame_update_tick: 0,
+                globe_sprite_frame_index: 0,
+                globe_last_sprite_frame_update_tick: 0,
             },
-            delta_time: 0.0,
-            last_frame_time: None,
             game_over: false,
+            won: false,
+            game_over_animation: GameOverAnimation {
+                frame: 0,
+                darkness: 0.5,
+                last_frame_update_tick: 0,
+            },
             score: 0,
-            food_score_value: 100,
+            food_score_value: BASE_FOOD_SCORE_VALUE,
+            food_eaten: None,
+            combo: Combo::default(),
             last_loot_spawn_score: 0,
+            last_loot_crate_check_tick: 0,
             powerup_eligibility: false,
+            in_powerup_selection: false,
+            highlighted_powerup: None,
+            powerup_selection_keys: HashMap::new(),
+            powerup_offers: Vec::new(),
             selected_powerup: None,
-            powerup_history: std::collections::HashMap::new(),
+            powerup_history: Vec::new(),
+            swap_charges: 0,
+            base_stats,
+            active_effects: Vec::new(),
+            spicy_ticks: 0,
+        };
+
+        // Grid mode starts with the worm on consecutive cells and the food snapped to a cell
+        if config.grid_mode {
+            state.player = grid::spawn_snake(state.level.spawn, state.level.spawn_direction);
+            state.food[0].position = Cell::of(state.food[0].position).position();
+        }
+        state.player.move_interval = base_stats.move_interval;
+
+        // Levels with fixed food spots, or an obstacle where the food starts, place it like a respawn
+        let level = &state.level;
+        let food_blocked = level.obstacles.iter().any(|obstacle| obstacle.overlaps(state.food[0].position, (ITEM_SIZE, ITEM_SIZE)));
+        if !level.food_spots.is_empty() || food_blocked {
+            let placed = placement::find_free_position(
+                &state.player,
+                &[],
+                &level.food_spots,
+                &level.obstacles,
+                config.grid_mode,
+                &mut state.rng,
+            );
+            match placed {
+                Some(position) => state.food[0].position = position,
+                None => state.food[0].is_active = false,
+            }
+        }
+
+        // The rest of the food items start out like respawns, clear of the ones already placed
+        while state.food.len() < config.food_items() {
+            let avoid = placement::occupied_positions(&state.food, &state.loot_crate, None);
+            let mut food = Food::new(Vector2D { x: 0.0, y: 0.0 });
+            if !placement::respawn_food(&mut food, &state.player, &avoid, &state.level, config.grid_mode, 0, &mut state.rng) {
+                break; // No room left on this level
+            }
+            state.food.push(food);
         }
+        state
     }
 
+    /// Resets the run to its starting state. The tick counter keeps counting and the RNG is
+    /// reseeded from `seed`, so every run with the same seed plays out the same sequence; casual
+    /// play picks a new seed before each run (see `Simulation::set_fresh_seed_each_run`).
     pub fn restart_level(&mut self) {
-        self.player = Snake::new(40.0, 150.0, Direction::Right);
-        self.food = Food {
-            position: Vector2D { x: 100.0, y: 100.0 },
-            is_active: false,
-            food_sprite_frame_index: 0,
-            food_last_sprite_frame_index_update_time: 0.0,
-        };
-        self.loot_crate = LootCrate {
-            position: Vector2D { x: 0.0, y: 0.0 },
-            is_active: false,
-            sprite_frame_index: 0,
-            last_sprite_frame_index_update_time: 0.0,
-        };
-        self.score = 0;
-        self.game_over = false;
-        self.last_loot_spawn_score = 0;
-        self.powerup_eligibility = false;
-        self.selected_powerup = None;
-        self.powerup_history.clear();
+        let current_tick = self.tick_count;
+        let campaign_stage = self.campaign_stage;
+        // The next run starts without the asteroids the ramp added to this one
+        let level_obstacles = self.level.obstacles.len() - self.hazards;
+        self.level.obstacles.truncate(level_obstacles);
+        *self = GameState::with_level(self.seed, self.config, std::mem::take(&mut self.level));
+        self.campaign_stage = campaign_stage;
+        self.tick_count = current_tick;
+        self.run_start_tick = current_tick;
+
+        // Restart all timers from the current tick
+        for food in self.food.iter_mut() {
+            food.food_last_sprite_frame_index_update_tick = current_tick;
+        }
+        self.loot_crate.last_sprite_frame_index_update_tick = current_tick;
+        self.background.stars_last_sprite_frame_update_tick = current_tick;
+        self.background.globe_last_sprite_frame_update_tick = current_tick;
+        self.game_over_animation.last_frame_update_tick = current_tick;
+        self.last_loot_crate_check_tick = current_tick;
+    }
+
+    /// Switches to `level`, dropping the asteroids the difficulty ramp added to the current one.
+    pub fn set_level(&mut self, level: Level) {
+        self.level = level;
+        self.hazards = 0;
+    }
+
+    /// Number of ticks the current run has lasted so far.
+    pub fn run_ticks(&self) -> u64 {
+        self.tick_count - self.run_start_tick
+    }
+
+    /// Whether leaving the playfield ends the run, either by the player's rules or the level's.
+    pub fn walls(&self) -> bool {
+        self.config.walls || !self.level.wrap
+    }
+
+    /// How much faster the worm moves than its base speed, from active perks and spicy food.
+    pub fn speed_multiplier(&self) -> f32 {
+        self.base_stats.move_interval / (self.player.move_interval * self.difficulty_speed())
+    }
+
+    /// Speed factor the difficulty ramp adds on top of the perks. Campaign stages set their own
+    /// pace, so they ignore it.
+    pub fn difficulty_speed(&self) -> f32 {
+        let step = crate::state::core::difficulty::current_step(self);
+        crate::state::core::difficulty::active_definition(self).map_or(1.0, |definition| definition.speed(step))
+    }
+
+    /// How much more each food is worth than its base value, from active perks.
+    pub fn score_multiplier(&self) -> f32 {
+        self.food_score_value as f32 / self.base_stats.food_score_value as f32
     }
 }
@@ -0,0 +1,984 @@
+use std::collections::{HashMap, HashSet};
+
+use space_worm::graphics::update::{powerup_card_at, powerup_card_bounds};
+use space_worm::platform::ManualClock;
+use space_worm::state::campaign::{stage, STAGES};
+use space_worm::state::codec::DecodeError;
+use space_worm::state::config::{DecayCurve, GameConfig};
+use space_worm::state::grid::Cell;
+use space_worm::state::level::{builtin_level, Goal, Level, Obstacle, ObstacleKind};
+use space_worm::state::placement::find_free_position;
+use space_worm::state::replay::{Replay, REPLAY_VERSION};
+use space_worm::state::save::SAVE_VERSION;
+use space_worm::state::rng::SeededRng;
+use space_worm::state::constants::state::LOOT_CRATE_SPAWN_CHANCE;
+use space_worm::state::constants::timing::{
+    COMBO_WINDOW_TICKS, HUNGRY_WORM_TICKS, MAX_FRAME_TIME_MS, NEED_FOR_SPEED_TICKS, SPICY_TICKS, TICK_DURATION_MS,
+};
+use space_worm::state::simulation::{Simulation, SimulationEvent};
+use space_worm::state::core::food::{food_value, is_visible, roll_food_kind, FoodKind, FOOD_KINDS};
+use space_worm::state::core::combo::Combo;
+use space_worm::state::core::difficulty::{self, ramp_step, Difficulty, DIFFICULTIES};
+use space_worm::state::core::effects::{recalculate_stats, update_active_effects};
+use space_worm::state::core::perks::{roll_powerup_offers, Perk, PERKS};
+use space_worm::state::structs::{Direction, Vector2D};
+
+// Ticks per snake move at the default move interval
+const MOVE_TICKS: usize = 12;
+
+fn new_game() -> Simulation<ManualClock> {
+    Simulation::new(ManualClock::new(0.0), 7)
+}
+
+fn run_moves(sim: &mut Simulation<ManualClock>, moves: usize) -> Vec<SimulationEvent> {
+    let mut events = Vec::new();
+    for _ in 0..moves * MOVE_TICKS {
+        sim.tick();
+        events.extend(sim.drain_events());
+    }
+    events
+}
+
+fn place_food_ahead(sim: &mut Simulation<ManualClock>) {
+    let head = sim.state.player.body[0];
+    sim.state.food[0].position = Vector2D { x: head.x + 6.0, y: head.y };
+    sim.state.food[0].kind = FoodKind::Standard;
+    sim.state.food[0].is_active = true;
+}
+
+// Puts the game on the selection screen as if a crate offering `offers` was just eaten, with the
+// first card highlighted
+fn open_selection(sim: &mut Simulation<ManualClock>, offers: Vec<Perk>) {
+    sim.state.in_powerup_selection = true;
+    sim.state.powerup_offers = offers;
+    sim.state.highlighted_powerup = Some(1);
+}
+
+fn pick_perk(sim: &mut Simulation<ManualClock>, perk: Perk) {
+    open_selection(sim, vec![perk]);
+    sim.handle_key_down("Space");
+    sim.tick();
+}
+
+#[test]
+fn snake_moves_without_a_browser() {
+    let mut sim = new_game();
+    let start_x = sim.state.player.body[0].x;
+
+    run_moves(&mut sim, 3);
+
+    assert!(sim.state.player.body[0].x > start_x);
+    assert!(!sim.state.game_over);
+}
+
+#[test]
+fn eating_food_scores_and_grows_the_worm() {
+    let mut sim = new_game();
+    place_food_ahead(&mut sim);
+
+    let events = run_moves(&mut sim, 1);
+
+    assert!(events.contains(&SimulationEvent::FoodEaten(FoodKind::Standard)));
+    assert_eq!(sim.state.score, 100);
+    assert_eq!(sim.state.player.body.len(), 4);
+}
+
+#[test]
+fn full_game_ends_in_self_collision_and_restarts() {
+    let mut sim = new_game();
+
+    // Grow to five segments so a tight turn can bite the body
+    for _ in 0..2 {
+        place_food_ahead(&mut sim);
+        run_moves(&mut sim, 1);
+    }
+    run_moves(&mut sim, 5);
+    assert_eq!(sim.state.player.body.len(), 5);
+
+    let mut events = Vec::new();
+    for key in ["KeyW", "KeyA", "KeyS"] {
+        sim.handle_key_down(key);
+        events.extend(run_moves(&mut sim, 1));
+    }
+    assert!(events.contains(&SimulationEvent::GameOver));
+    assert!(sim.state.game_over);
+
+    // The game over animation restarts the run on its own
+    let events = run_moves(&mut sim, 50);
+    assert!(events.contains(&SimulationEvent::Restarted));
+    assert!(!sim.state.game_over);
+    assert_eq!(sim.state.score, 0);
+}
+
+#[test]
+fn same_seed_produces_same_food_and_crate_sequence() {
+    fn food_positions(seed: u64) -> Vec<(f32, f32)> {
+        let mut sim = Simulation::new(ManualClock::new(0.0), seed);
+        let mut positions = Vec::new();
+        for _ in 0..5 {
+            place_food_ahead(&mut sim);
+            run_moves(&mut sim, 1);
+            positions.push((sim.state.food[0].position.x, sim.state.food[0].position.y));
+        }
+        positions
+    }
+
+    assert_eq!(food_positions(42), food_positions(42));
+    assert_ne!(food_positions(42), food_positions(43));
+}
+
+#[test]
+fn wall_clock_frames_are_split_into_fixed_ticks() {
+    let mut sim = new_game();
+
+    // The first frame only primes the frame timer
+    sim.step();
+    sim.clock.advance(TICK_DURATION_MS * 3.5);
+    sim.step();
+    assert_eq!(sim.state.tick_count, 3);
+    assert!((sim.interpolation_alpha() - 0.5).abs() < 0.01);
+
+    // A long stall (throttled tab) is clamped instead of fast-forwarding the game
+    sim.clock.advance(60_000.0);
+    sim.step();
+    assert!(sim.state.tick_count <= 3 + (MAX_FRAME_TIME_MS / TICK_DURATION_MS) as u64 + 1);
+}
+
+#[test]
+fn recorded_run_replays_identically() {
+    let mut sim = new_game();
+    for key in ["KeyS", "KeyD", "KeyW", "KeyD", "KeyS", "KeyA"] {
+        run_moves(&mut sim, 7);
+        sim.handle_key_down(key);
+    }
+    run_moves(&mut sim, 3);
+
+    let bytes = sim.export_replay().to_bytes();
+    let replay = Replay::from_bytes(&bytes).unwrap();
+    assert_eq!(replay.inputs.len(), 6);
+    assert_eq!(replay.ticks, sim.state.run_ticks());
+
+    let mut replayed = Simulation::new(ManualClock::new(0.0), 1);
+    replayed.load_replay(replay.clone());
+    for _ in 0..replay.ticks {
+        replayed.tick();
+    }
+
+    assert_eq!(replayed.state.player.body, sim.state.player.body);
+    assert_eq!(replayed.state.score, sim.state.score);
+    assert_eq!(replayed.state.rng, sim.state.rng);
+    assert_eq!(replayed.current_replay(), sim.current_replay());
+
+    // Casual play moves on to a new seed each run, and the replay keeps the one it ran on
+    let mut casual = new_game();
+    casual.set_fresh_seed_each_run(true);
+    casual.restart();
+    assert_ne!(casual.state.seed, 7);
+    casual.handle_key_down("KeyS");
+    run_moves(&mut casual, 5);
+    let replay = casual.current_replay();
+    assert_eq!(replay.seed, casual.state.seed);
+    casual.load_replay(replay.clone());
+    assert_eq!(casual.state.seed, replay.seed, "playing a replay back keeps its seed");
+    assert_eq!(Simulation::run_replay(&replay).score, replay.score);
+}
+
+#[test]
+fn replay_from_a_newer_format_is_rejected() {
+    let mut bytes = Replay::new(7, GameConfig::default()).to_bytes();
+    bytes[4] += 1;
+
+    assert_eq!(
+        Replay::from_bytes(&bytes),
+        Err(DecodeError::UnsupportedVersion { found: REPLAY_VERSION + 1, expected: REPLAY_VERSION })
+    );
+    assert_eq!(Replay::from_bytes(b"nope"), Err(DecodeError::BadMagic));
+}
+
+#[test]
+fn headless_verifier_reproduces_the_claimed_score() {
+    let mut sim = new_game();
+    place_food_ahead(&mut sim);
+    run_moves(&mut sim, 2);
+    sim.handle_key_down("KeyS");
+    run_moves(&mut sim, 4);
+    let replay = sim.export_replay();
+
+    // The food was placed by hand, so the honest replay cannot reproduce that score
+    let outcome = Simulation::run_replay(&replay);
+    assert_eq!(outcome.ticks, replay.ticks);
+    assert_eq!(outcome.length, 3);
+    assert_ne!(outcome.score, replay.score);
+
+    let mut honest = Simulation::new(ManualClock::new(0.0), replay.seed);
+    honest.handle_key_down("KeyD");
+    run_moves(&mut honest, 6);
+    let replay = honest.export_replay();
+    let outcome = Simulation::run_replay(&replay);
+    assert_eq!(outcome.score, replay.score);
+    assert_eq!(outcome.length, honest.state.player.body.len());
+    assert!(!outcome.game_over);
+}
+
+#[test]
+fn saved_run_resumes_exactly_where_it_left_off() {
+    let mut sim = new_game();
+    place_food_ahead(&mut sim);
+    run_moves(&mut sim, 3);
+    sim.handle_key_down("KeyS");
+    run_moves(&mut sim, 2);
+    let saved = sim.save_state();
+
+    let mut resumed = Simulation::new(ManualClock::new(0.0), 99);
+    resumed.load_state(&saved).unwrap();
+    assert_eq!(resumed.state.score, 100);
+    assert_eq!(resumed.state.player.body, sim.state.player.body);
+
+    // Both copies carry on identically, including the RNG and the input log
+    for sim in [&mut sim, &mut resumed] {
+        sim.handle_key_down("KeyD");
+        run_moves(sim, 20);
+    }
+    assert_eq!(resumed.state.player.body, sim.state.player.body);
+    assert_eq!(resumed.state.food[0].position, sim.state.food[0].position);
+    assert_eq!(resumed.state.rng, sim.state.rng);
+    assert_eq!(resumed.current_replay(), sim.current_replay());
+}
+
+#[test]
+fn save_from_another_version_is_rejected() {
+    let mut sim = new_game();
+    let mut saved = sim.save_state();
+    saved[4] = 0;
+
+    assert_eq!(
+        sim.load_state(&saved),
+        Err(DecodeError::UnsupportedVersion { found: 0, expected: SAVE_VERSION })
+    );
+    assert_eq!(sim.state.seed, 7);
+}
+
+#[test]
+fn escape_pauses_and_resumes_normal_play() {
+    let mut sim = new_game();
+    run_moves(&mut sim, 1);
+
+    sim.handle_key_down("Escape");
+    assert!(sim.is_paused());
+    assert_eq!(sim.drain_events(), vec![SimulationEvent::Paused]);
+
+    // Nothing moves while paused, and steering is ignored
+    let head = sim.state.player.body[0];
+    let tick_count = sim.state.tick_count;
+    sim.handle_key_down("KeyS");
+    run_moves(&mut sim, 5);
+    sim.advance(1_000.0);
+    assert_eq!(sim.state.player.body[0], head);
+    assert_eq!(sim.state.tick_count, tick_count);
+
+    sim.handle_key_down("Escape");
+    assert!(!sim.is_paused());
+    assert_eq!(sim.drain_events(), vec![SimulationEvent::Resumed]);
+    run_moves(&mut sim, 1);
+    assert!(sim.state.player.body[0].x > head.x);
+    assert!(sim.current_replay().inputs.is_empty());
+}
+
+#[test]
+fn grid_mode_keeps_the_worm_on_whole_cells() {
+    let mut sim = Simulation::with_config(ManualClock::new(0.0), 7, GameConfig { grid_mode: true, ..GameConfig::default() });
+    let on_grid = |v: Vector2D| v.x % 6.0 == 0.0 && v.y % 8.0 == 0.0;
+
+    for key in ["KeyS", "KeyA", "KeyW", "KeyA"] {
+        sim.handle_key_down(key);
+        run_moves(&mut sim, 30);
+        assert!(sim.state.player.body.iter().all(|&segment| on_grid(segment)));
+    }
+    assert!(on_grid(sim.state.food[0].position));
+}
+
+#[test]
+fn grid_mode_eats_and_collides_on_exact_cells() {
+    let mut sim = Simulation::with_config(ManualClock::new(0.0), 7, GameConfig { grid_mode: true, ..GameConfig::default() });
+
+    // Food one cell ahead is eaten on the next move and respawns on a free cell
+    for _ in 0..2 {
+        let head = sim.state.player.body[0];
+        sim.state.food[0].position = Vector2D { x: head.x + 6.0, y: head.y };
+        sim.state.food[0].kind = FoodKind::Standard;
+        let events = run_moves(&mut sim, 1);
+        assert!(events.contains(&SimulationEvent::FoodEaten(FoodKind::Standard)));
+        assert!(!sim.state.player.body.contains(&sim.state.food[0].position));
+    }
+    run_moves(&mut sim, 4);
+    assert_eq!(sim.state.player.body.len(), 5);
+
+    // A tight U-turn lands the head exactly on its own body
+    let mut events = Vec::new();
+    for key in ["KeyW", "KeyA", "KeyS"] {
+        sim.handle_key_down(key);
+        events.extend(run_moves(&mut sim, 1));
+    }
+    assert!(events.contains(&SimulationEvent::GameOver));
+}
+
+#[test]
+fn placement_keeps_items_clear_of_the_worm_and_hud() {
+    let mut sim = new_game();
+    let player = &sim.state.player;
+    let head = player.body[0];
+    let crate_position = Vector2D { x: 100.0, y: 100.0 };
+
+    for _ in 0..200 {
+        let position = find_free_position(player, &[crate_position], &[], &[], false, &mut sim.state.rng).unwrap();
+        let distance = |a: Vector2D, b: Vector2D| ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt();
+
+        assert!(position.y >= 24.0, "food behind the HUD at {:?}", position);
+        assert!(position.x + 16.0 <= 256.0 && position.y + 16.0 <= 224.0);
+        assert!(distance(position, head) >= 36.0);
+        assert!(player.body.iter().all(|&segment| distance(position, segment) >= 12.0));
+        assert!(distance(position, crate_position) >= 16.0);
+    }
+}
+
+#[test]
+fn filling_the_board_wins_the_run() {
+    let mut sim = Simulation::with_config(ManualClock::new(0.0), 7, GameConfig { grid_mode: true, ..GameConfig::default() });
+
+    // The worm covers every cell except the food just ahead of its head; its tail sits in the HUD
+    let head = Cell { col: 10, row: 10 };
+    let food = Cell { col: 11, row: 10 };
+    let tail = Cell { col: 0, row: 0 };
+    let mut body = vec![head.position()];
+    for row in 0..28 {
+        for col in 0..42 {
+            let cell = Cell { col, row };
+            if cell != head && cell != food && cell != tail {
+                body.push(cell.position());
+            }
+        }
+    }
+    body.push(tail.position());
+    sim.state.player.body = body;
+    sim.state.food[0].position = food.position();
+
+    let events = run_moves(&mut sim, 1);
+    assert!(events.contains(&SimulationEvent::Won));
+    assert!(!events.contains(&SimulationEvent::GameOver));
+    assert!(sim.state.game_over && sim.state.won);
+}
+
+#[test]
+fn reverso_turns_the_tail_into_the_head() {
+    let mut sim = new_game();
+    run_moves(&mut sim, 2);
+    sim.handle_key_down("KeyS");
+    run_moves(&mut sim, 1);
+    let old_body = sim.state.player.body.clone();
+
+    // Open a crate offering Reverso and take it
+    pick_perk(&mut sim, Perk::Reverso);
+
+    assert!(sim.drain_events().contains(&SimulationEvent::PowerupSelectionEnded(Some(Perk::Reverso))));
+    let mut reversed = old_body.clone();
+    reversed.reverse();
+    assert_eq!(sim.state.player.body, reversed);
+
+    // The tail was trailing to the left of the bend, so the new head moves left
+    assert_eq!(sim.state.player.direction, Direction::Left);
+    let head_x = sim.state.player.body[0].x;
+    run_moves(&mut sim, 1);
+    assert!(sim.state.player.body[0].x < head_x);
+    assert!(!sim.state.game_over);
+}
+
+#[test]
+fn swap_charges_move_the_worm_onto_the_food() {
+    let mut sim = new_game();
+
+    // Without charges the swap key does nothing
+    sim.handle_key_down("KeyE");
+    assert!(sim.drain_events().is_empty());
+
+    pick_perk(&mut sim, Perk::Swap);
+    sim.drain_events();
+    assert_eq!(sim.state.swap_charges, 2);
+
+    let head = sim.state.player.body[0];
+    let neck = sim.state.player.body[1];
+    let food = sim.state.food[0].position;
+    sim.handle_key_down("KeyE");
+
+    assert_eq!(sim.drain_events(), vec![SimulationEvent::SwapTriggered]);
+    assert_eq!(sim.state.player.body[0], food);
+    assert_eq!(sim.state.food[0].position, head);
+    assert_eq!(sim.state.swap_charges, 1);
+
+    // The body keeps its shape behind the head
+    let moved = sim.state.player.body[1];
+    assert_eq!((moved.x - food.x, moved.y - food.y), (neck.x - head.x, neck.y - head.y));
+}
+
+#[test]
+fn swap_refuses_to_land_across_a_wall_or_on_an_obstacle() {
+    let walled = GameConfig { walls: true, ..GameConfig::default() };
+    let mut sim = Simulation::with_config(ManualClock::new(0.0), 7, walled);
+    sim.state.swap_charges = 2;
+    let body = sim.state.player.body.clone();
+    let head = body[0];
+
+    // Food hugging the left wall would push the trailing tail off the playfield
+    sim.state.food[0].position = Vector2D { x: 4.0, y: head.y };
+    sim.handle_key_down("KeyE");
+    assert!(sim.drain_events().is_empty());
+    assert_eq!((sim.state.player.body.clone(), sim.state.swap_charges), (body.clone(), 2));
+
+    // So would landing the tail on an asteroid
+    let food = Vector2D { x: 120.0, y: head.y };
+    sim.state.food[0].position = food;
+    let tail = body[body.len() - 1];
+    let landing = Vector2D { x: tail.x + food.x - head.x, y: tail.y };
+    sim.state.level.obstacles.push(Obstacle { kind: ObstacleKind::Asteroid, cell: Cell::of(landing) });
+    sim.handle_key_down("KeyE");
+    assert_eq!(sim.state.swap_charges, 2);
+
+    // With the way clear the swap goes through
+    sim.state.level.obstacles.clear();
+    sim.handle_key_down("KeyE");
+    assert_eq!(sim.drain_events(), vec![SimulationEvent::SwapTriggered]);
+    assert_eq!(sim.state.player.body[0], food);
+    assert_eq!(sim.state.swap_charges, 1);
+}
+
+#[test]
+fn loot_crates_offer_distinct_weighted_perks() {
+    let mut rng = SeededRng::new(11);
+    let mut sizes = HashSet::new();
+    let mut picks: HashMap<Perk, u32> = HashMap::new();
+    for _ in 0..2000 {
+        let offers = roll_powerup_offers(&mut rng);
+        sizes.insert(offers.len());
+        let distinct: HashSet<Perk> = offers.iter().copied().collect();
+        assert_eq!(distinct.len(), offers.len());
+        for perk in offers {
+            *picks.entry(perk).or_default() += 1;
+        }
+    }
+    assert_eq!(sizes, HashSet::from([2, 3]));
+
+    // Every registered perk shows up, and rare ones less often than common ones
+    assert!(PERKS.iter().all(|definition| picks.contains_key(&definition.perk)));
+    assert!(picks[&Perk::Swap] < picks[&Perk::Reverso]);
+    assert!(picks[&Perk::Reverso] < picks[&Perk::NeedForSpeed]);
+
+    // The same seed rolls the same offers
+    assert_eq!(roll_powerup_offers(&mut SeededRng::new(5)), roll_powerup_offers(&mut SeededRng::new(5)));
+}
+
+#[test]
+fn selection_navigates_across_three_cards() {
+    let mut sim = new_game();
+    open_selection(&mut sim, vec![Perk::HungryWorm, Perk::Reverso, Perk::Swap]);
+
+    for key in ["KeyD", "KeyD", "KeyD", "Space"] {
+        sim.handle_key_down(key);
+        sim.tick();
+    }
+
+    // The highlight stops at the last card
+    assert!(sim.drain_events().contains(&SimulationEvent::PowerupSelectionEnded(Some(Perk::Swap))));
+    assert_eq!(sim.state.swap_charges, 2);
+}
+
+#[test]
+fn timed_perks_stack_and_wear_off() {
+    let mut sim = new_game();
+
+    // Need 4 Speed stacks up to twice, however often it is picked
+    for _ in 0..3 {
+        pick_perk(&mut sim, Perk::NeedForSpeed);
+    }
+    assert_eq!(sim.state.active_effects[0].stacks, 2);
+    assert!((sim.state.player.move_interval - 0.1 * 0.75 * 0.75).abs() < 1e-6);
+
+    // Hungry Worm adds its duration on every pick instead of doubling again
+    pick_perk(&mut sim, Perk::HungryWorm);
+    pick_perk(&mut sim, Perk::HungryWorm);
+    assert_eq!(sim.state.food_score_value, 200);
+    let hungry = sim.state.active_effects.iter().find(|effect| effect.perk == Perk::HungryWorm).unwrap();
+    assert_eq!(hungry.remaining_ticks, Some(2 * HUNGRY_WORM_TICKS));
+
+    // Run the clocks down without moving the worm
+    for _ in 0..NEED_FOR_SPEED_TICKS {
+        update_active_effects(&mut sim.state.active_effects);
+    }
+    recalculate_stats(&mut sim.state);
+    assert_eq!(sim.state.active_effects.len(), 1);
+    assert!((sim.state.player.move_interval - 0.1).abs() < 1e-6);
+    assert_eq!(sim.state.food_score_value, 200);
+
+    for _ in NEED_FOR_SPEED_TICKS..2 * HUNGRY_WORM_TICKS {
+        update_active_effects(&mut sim.state.active_effects);
+    }
+    recalculate_stats(&mut sim.state);
+    assert!(sim.state.active_effects.is_empty());
+    assert_eq!(sim.state.food_score_value, 100);
+}
+
+#[test]
+fn hud_multipliers_follow_active_perks() {
+    let mut sim = new_game();
+    assert_eq!((sim.state.speed_multiplier(), sim.state.score_multiplier()), (1.0, 1.0));
+
+    pick_perk(&mut sim, Perk::NeedForSpeed);
+    pick_perk(&mut sim, Perk::HungryWorm);
+    assert!((sim.state.speed_multiplier() - 1.0 / 0.75).abs() < 1e-4);
+    assert_eq!(sim.state.score_multiplier(), 2.0);
+}
+
+#[test]
+fn selection_accepts_number_keys_arrows_and_card_clicks() {
+    let mut sim = new_game();
+    open_selection(&mut sim, vec![Perk::HungryWorm, Perk::Reverso, Perk::NeedForSpeed, Perk::Swap]);
+
+    // Arrow keys move the highlight like A and D
+    for key in ["ArrowRight", "ArrowRight", "ArrowLeft"] {
+        sim.handle_key_down(key);
+        sim.tick();
+    }
+    assert_eq!(sim.state.highlighted_powerup, Some(2));
+
+    // A number key picks its card straight away, whatever is highlighted
+    sim.handle_key_down("Digit4");
+    sim.tick();
+    assert!(sim.drain_events().contains(&SimulationEvent::PowerupSelectionEnded(Some(Perk::Swap))));
+
+    // Four cards share the width; each click lands on the card under it
+    let (x, y, width, height) = powerup_card_bounds(3, 4);
+    assert_eq!(powerup_card_at((x + width / 2) as f32, (y + height / 2) as f32, 4), Some(3));
+    assert_eq!(powerup_card_at(10.0, 10.0, 4), None);
+    assert!((1..=4).all(|card| powerup_card_bounds(card, 4).0 + powerup_card_bounds(card, 4).2 <= 256));
+}
+
+#[test]
+fn walls_end_the_run_instead_of_wrapping() {
+    for grid_mode in [false, true] {
+        let config = GameConfig { grid_mode, walls: true, ..GameConfig::default() };
+        let mut sim = Simulation::with_config(ManualClock::new(0.0), 7, config);
+
+        // Head straight for the right edge
+        let mut events = Vec::new();
+        while !sim.state.game_over {
+            assert!(sim.state.player.body[0].x < 256.0, "worm wrapped in wall mode");
+            sim.tick();
+            events.extend(sim.drain_events());
+        }
+
+        assert!(!sim.state.won);
+        assert!(events.contains(&SimulationEvent::GameOver));
+        assert!(sim.state.player.body[0].x + 6.0 > 256.0);
+    }
+}
+
+#[test]
+fn obstacles_from_the_level_kill_the_worm_and_block_spawns() {
+    let wall = |col| Obstacle { kind: ObstacleKind::StationWall, cell: Cell { col, row: 19 } };
+    let level = Level { name: "Dead End".to_string(), obstacles: vec![wall(10), wall(11)], ..Level::default() };
+
+    let mut sim = new_game();
+    sim.load_level(level.clone());
+    let events = run_moves(&mut sim, 3);
+    assert!(events.contains(&SimulationEvent::GameOver));
+
+    // The level travels with the replay, so the verifier crashes at the same tick
+    let replay = Replay::from_bytes(&sim.export_replay().to_bytes()).unwrap();
+    assert_eq!(replay.level, level);
+    let outcome = Simulation::run_replay(&replay);
+    assert!(outcome.game_over && !outcome.won);
+    assert_eq!(outcome.ticks, replay.ticks);
+
+    // Cells off the top-left edge survive the trip as well
+    let offset = Level { obstacles: vec![Obstacle { kind: ObstacleKind::Asteroid, cell: Cell { col: -3, row: i32::MIN } }], ..level };
+    let replay = Replay::with_level(7, GameConfig::default(), offset.clone(), None);
+    assert_eq!(Replay::from_bytes(&replay.to_bytes()).unwrap().level, offset);
+
+    // Nothing spawns on top of a built-in layout's obstacles
+    let station = builtin_level(2).unwrap();
+    let player = &sim.state.player;
+    for _ in 0..200 {
+        let position = find_free_position(player, &[], &[], &station.obstacles, false, &mut sim.state.rng).unwrap();
+        assert!(station.obstacles.iter().all(|obstacle| !obstacle.overlaps(position, (16.0, 16.0))));
+    }
+}
+
+#[test]
+fn text_levels_set_the_start_rules_and_item_spots() {
+    let text = "name: Short Hop\nwrap: off\ntarget: 100\nspeed: 20\n\n\
+                ..........\n\
+                ...S>.F...\n\
+                ....C....*\n";
+    let level = Level::parse(text).unwrap();
+    assert_eq!(level.name, "Short Hop");
+    assert!(!level.wrap);
+    assert_eq!(level.goal, Some(Goal::Score(100)));
+    assert_eq!((level.spawn, level.spawn_direction), (Cell { col: 3, row: 4 }, Direction::Right));
+    assert_eq!(level.food_spots, vec![Cell { col: 6, row: 4 }]);
+    assert_eq!(level.crate_spots, vec![Cell { col: 4, row: 5 }]);
+    assert_eq!(level.obstacles, vec![Obstacle { kind: ObstacleKind::Asteroid, cell: Cell { col: 9, row: 5 } }]);
+
+    // The worm starts on the spawn point at the level's speed, with food on its fixed spot
+    let mut sim = new_game();
+    sim.load_level(level);
+    assert_eq!(sim.state.player.body[0], Cell { col: 3, row: 4 }.position());
+    assert_eq!(sim.state.player.move_interval, 0.05);
+    assert_eq!(sim.state.food[0].position, Cell { col: 6, row: 4 }.position());
+    assert!(sim.state.walls());
+
+    // Eating the food reaches the target score and clears the level
+    run_moves(&mut sim, 3);
+    assert!(sim.state.won);
+    assert_eq!(sim.state.score, 100);
+
+    // Every built-in level is written in the same format
+    assert_eq!(builtin_level(0), Some(Level::default()));
+    assert!((0..3).all(|index| builtin_level(index).is_some()));
+}
+
+#[test]
+fn level_parse_errors_point_at_the_line_and_column() {
+    let error = |text: &str| Level::parse(text).unwrap_err().to_string();
+
+    let bad_speed = Level::parse("name: Broken\nspeed: fast\n\n.S>\n").unwrap_err();
+    assert_eq!((bad_speed.line, bad_speed.column), (2, 8));
+    assert_eq!(bad_speed.to_string(), "line 2, column 8: speed must be between 1 and 60");
+
+    assert_eq!(error("name: Broken\n\n....S>\n..?...\n"), "line 4, column 3: unknown map symbol `?`");
+    assert_eq!(error("name: Broken\n\n....S.\n"), "line 3, column 5: the spawn point needs an arrow next to it");
+    assert_eq!(
+        error("name: Broken\n\n.S>....\n"),
+        "line 3, column 2: there is no room for the worm behind the spawn point"
+    );
+    assert_eq!(error("name: Broken\n\n......\n"), "line 3, column 1: the map has no spawn point `S`");
+    assert_eq!(error("wrap: off\n\n....S>\n"), "line 1, column 1: the header needs a `name`");
+}
+
+#[test]
+fn clearing_a_campaign_stage_unlocks_and_loads_the_next() {
+    assert!(STAGES.iter().all(|text| Level::parse(text).unwrap().goal.is_some()));
+
+    let mut sim = new_game();
+    assert!(!sim.start_campaign(1), "later stages start locked");
+    assert!(sim.start_campaign(0));
+    let Some(Goal::Score(target)) = sim.state.level.goal else { panic!("stage 1 is a score stage") };
+
+    // One more food reaches the goal
+    sim.state.score = target - 100;
+    place_food_ahead(&mut sim);
+    let events = run_moves(&mut sim, 2);
+    assert!(events.contains(&SimulationEvent::StageCleared(0)));
+    assert!(sim.state.won);
+    assert_eq!(sim.campaign_unlocked(), 1);
+
+    // Leaving the stage clear screen starts the next stage; the campaign survives a save
+    sim.handle_key_down("Space");
+    assert_eq!(sim.state.campaign_stage, Some(1));
+    assert_eq!(sim.state.level, stage(1).unwrap());
+    let saved = sim.save_state();
+    let mut resumed = new_game();
+    resumed.load_state(&saved).unwrap();
+    assert_eq!(resumed.state.campaign_stage, Some(1));
+
+    // Clearing the last stage returns to open space
+    sim.set_campaign_unlocked(99);
+    assert_eq!(sim.campaign_unlocked(), STAGES.len() - 1);
+    assert!(sim.start_campaign(STAGES.len() - 1));
+    sim.state.game_over = true;
+    sim.state.won = true;
+    sim.handle_key_down("Space");
+    assert_eq!(sim.state.campaign_stage, None);
+    assert_eq!(sim.state.level, Level::default());
+
+    // Survival goals count whole seconds of the run
+    assert!(!Goal::Survive(45).is_met(0, 3, 45 * 120 - 1));
+    assert!(Goal::Survive(45).is_met(0, 3, 45 * 120));
+    assert_eq!(Goal::Length(20).label(0, 7, 0), "LEN 7/20");
+
+    // ...of actual play: idling on the selection screen doesn't run the clock
+    assert!(sim.start_campaign(2));
+    let Some(Goal::Survive(seconds)) = sim.state.level.goal else { panic!("stage 3 is a survival stage") };
+    open_selection(&mut sim, vec![Perk::HungryWorm]);
+    for _ in 0..(seconds + 5) * 120 {
+        sim.tick();
+    }
+    assert!(!sim.state.game_over);
+    assert_eq!(sim.state.play_ticks, 0);
+}
+
+#[test]
+fn food_kinds_spawn_by_weight_and_feed_the_worm_differently() {
+    let mut rng = SeededRng::new(3);
+    let mut counts: HashMap<FoodKind, u64> = HashMap::new();
+    for _ in 0..10_000 {
+        *counts.entry(roll_food_kind(&mut rng)).or_default() += 1;
+    }
+    let total_weight: u64 = FOOD_KINDS.iter().map(|definition| definition.weight).sum();
+    for definition in &FOOD_KINDS {
+        let expected = 10_000 * definition.weight / total_weight;
+        let rolled = counts[&definition.kind];
+        assert!(rolled.abs_diff(expected) < expected / 4 + 20, "{:?}: {} vs {}", definition.kind, rolled, expected);
+    }
+
+    let eat = |sim: &mut Simulation<ManualClock>, kind| {
+        place_food_ahead(sim);
+        sim.state.food[0].kind = kind;
+        run_moves(sim, 1)
+    };
+
+    // Golden food is worth five standard ones
+    let mut sim = new_game();
+    let events = eat(&mut sim, FoodKind::Golden);
+    assert!(events.contains(&SimulationEvent::FoodEaten(FoodKind::Golden)));
+    assert_eq!((sim.state.score, sim.state.player.body.len()), (500, 4));
+
+    // Rotten food scores nothing and shrinks the worm, but never below its starting length. The
+    // standard bites in between come quickly enough to combo at x2, x3 and x4
+    for _ in 0..3 {
+        eat(&mut sim, FoodKind::Standard);
+    }
+    eat(&mut sim, FoodKind::Rotten);
+    assert_eq!((sim.state.score, sim.state.player.body.len()), (1400, 5));
+    eat(&mut sim, FoodKind::Rotten);
+    eat(&mut sim, FoodKind::Rotten);
+    assert_eq!(sim.state.player.body.len(), 3);
+
+    // Spicy food is a short burst of speed, separate from the perks
+    eat(&mut sim, FoodKind::Spicy);
+    assert!(sim.state.speed_multiplier() > 1.0);
+    assert!(sim.state.active_effects.is_empty());
+    for _ in 0..SPICY_TICKS {
+        sim.tick();
+    }
+    assert_eq!(sim.state.speed_multiplier(), 1.0);
+
+    // Cosmic food hands out a perk without the selection screen
+    eat(&mut sim, FoodKind::Cosmic);
+    assert_eq!(sim.state.powerup_history.len(), 1);
+    assert!(!sim.state.in_powerup_selection);
+
+    // Picking Need 4 Speed during a spicy burst is still a single stack, and the two add up
+    let mut spiced = new_game();
+    eat(&mut spiced, FoodKind::Spicy);
+    let spicy_speed = spiced.state.speed_multiplier();
+    pick_perk(&mut spiced, Perk::NeedForSpeed);
+    assert_eq!(spiced.state.active_effects[0].stacks, 1);
+    assert!(spiced.state.speed_multiplier() > spicy_speed);
+}
+
+#[test]
+fn several_food_items_share_the_board() {
+    let config = GameConfig { food_count: 4, ..GameConfig::default() };
+    let mut sim = Simulation::with_config(ManualClock::new(0.0), 7, config);
+    let spread_out = |sim: &Simulation<ManualClock>| {
+        let food = &sim.state.food;
+        food.iter().all(|item| item.is_active)
+            && food.iter().enumerate().all(|(i, a)| {
+                food[i + 1..].iter().all(|b| (a.position.x - b.position.x).abs() >= 16.0 || (a.position.y - b.position.y).abs() >= 16.0)
+            })
+    };
+    assert_eq!(sim.state.food.len(), 4);
+    assert!(spread_out(&sim));
+
+    // Eating one respawns just that one, clear of the others
+    place_food_ahead(&mut sim);
+    let untouched: Vec<Vector2D> = sim.state.food[1..].iter().map(|item| item.position).collect();
+    let events = run_moves(&mut sim, 1);
+    assert!(events.contains(&SimulationEvent::FoodEaten(FoodKind::Standard)));
+    assert_eq!(sim.state.food.len(), 4);
+    assert!(spread_out(&sim));
+    assert!(sim.state.food[1..].iter().map(|item| item.position).eq(untouched));
+
+    // Every item survives a save
+    let mut resumed = new_game();
+    resumed.load_state(&sim.save_state()).unwrap();
+    assert!(resumed.state.food.iter().map(|item| item.position).eq(sim.state.food.iter().map(|item| item.position)));
+
+    // Out-of-range counts are pulled into 1..=5
+    assert_eq!(GameConfig { food_count: 9, ..GameConfig::default() }.food_items(), 5);
+    assert_eq!(GameConfig { food_count: 0, ..GameConfig::default() }.food_items(), 1);
+}
+
+#[test]
+fn food_loses_value_with_age_then_blinks_and_moves() {
+    let config = GameConfig { food_lifetime: 8, ..GameConfig::default() };
+    let lifetime = config.food_lifetime_ticks().unwrap();
+    let mut sim = Simulation::with_config(ManualClock::new(0.0), 7, config);
+    let full = sim.state.food_score_value;
+
+    // Linear decay: full value when fresh, half way through the lifetime half of it
+    sim.state.food[0].kind = FoodKind::Standard;
+    assert_eq!(food_value(&sim.state.food[0], full, &sim.state.config), full);
+    sim.state.food[0].age_ticks = lifetime / 2;
+    assert_eq!(food_value(&sim.state.food[0], full, &sim.state.config), full / 2);
+    assert!(is_visible(&sim.state.food[0], &sim.state.config));
+
+    // Eating it scores what it is worth right now
+    place_food_ahead(&mut sim);
+    let score = sim.state.score;
+    run_moves(&mut sim, 1);
+    let gained = sim.state.score - score;
+    assert!(gained > 0 && gained <= full / 2);
+
+    // Close to the end it blinks, and once the lifetime is up it moves and starts over
+    let food = &mut sim.state.food[0];
+    food.age_ticks = lifetime - 1;
+    let blinks = (1..=lifetime).any(|age| {
+        food.age_ticks = age;
+        !is_visible(food, &sim.state.config)
+    });
+    assert!(blinks);
+    sim.state.food[0].age_ticks = lifetime - 1;
+    let position = sim.state.food[0].position;
+    sim.tick();
+    assert!(sim.state.food[0].is_active);
+    assert_ne!(sim.state.food[0].position, position);
+    assert!(sim.state.food[0].age_ticks < lifetime / 2);
+
+    // Without a lifetime food never decays or blinks
+    let mut forever = new_game();
+    forever.state.food[0].age_ticks = 1_000_000;
+    assert_eq!(food_value(&forever.state.food[0], full, &forever.state.config), full * forever.state.food[0].kind.definition().score_multiplier);
+    assert!(is_visible(&forever.state.food[0], &forever.state.config));
+
+    // The curves: exponential drops fast, stepped in quarters, and none reach zero
+    assert_eq!(DecayCurve::Linear.factor(0.25), 0.75);
+    assert_eq!(DecayCurve::Exponential.factor(0.5), 0.25);
+    assert_eq!(DecayCurve::Stepped.factor(0.6), 0.5);
+    assert!(DecayCurve::Exponential.factor(1.0) > 0.0);
+}
+
+#[test]
+fn quick_bites_build_a_combo_that_lapses() {
+    let mut sim = new_game();
+    let eat = |sim: &mut Simulation<ManualClock>| {
+        let score = sim.state.score;
+        place_food_ahead(sim);
+        run_moves(sim, 1);
+        sim.state.score - score
+    };
+
+    // Each bite inside the window scores one step higher
+    assert_eq!(eat(&mut sim), 100);
+    assert_eq!(eat(&mut sim), 200);
+    assert_eq!(eat(&mut sim), 300);
+    assert_eq!(sim.state.combo.multiplier(), 3);
+
+    // The combo survives a save
+    let mut resumed = new_game();
+    resumed.load_state(&sim.save_state()).unwrap();
+    assert_eq!(resumed.state.combo, sim.state.combo);
+
+    // Letting the window lapse starts over, but the best combo is kept for the game over screen
+    for _ in 0..COMBO_WINDOW_TICKS {
+        sim.tick();
+    }
+    assert_eq!(sim.state.combo.multiplier(), 1);
+    assert_eq!(eat(&mut sim), 100);
+    assert_eq!(sim.state.combo.best, 3);
+
+    // A rotten bite doesn't count towards the combo and breaks the one running
+    eat(&mut sim);
+    assert_eq!(sim.state.combo.multiplier(), 2);
+    place_food_ahead(&mut sim);
+    sim.state.food[0].kind = FoodKind::Rotten;
+    run_moves(&mut sim, 1);
+    assert_eq!(sim.state.combo.multiplier(), 1);
+    assert_eq!(eat(&mut sim), 100);
+
+    // The multiplier tops out
+    let mut combo = Combo::default();
+    for _ in 0..20 {
+        combo.record_bite();
+    }
+    assert_eq!(combo.next_multiplier(), 9);
+}
+
+#[test]
+fn difficulty_presets_ramp_up_and_keep_their_own_high_scores() {
+    let hard = GameConfig { difficulty: Difficulty::Hard, walls: true, ..GameConfig::default() };
+    let mut sim = Simulation::with_config(ManualClock::new(0.0), 7, hard);
+
+    // Presets start at their own speed, which the perk multiplier leaves out
+    sim.tick();
+    assert!((sim.state.difficulty_speed() - 1.2).abs() < 1e-4);
+    assert_eq!(sim.state.speed_multiplier(), 1.0);
+
+    // Points and length ramp the run up: faster, fewer crates, more asteroids
+    assert_eq!(ramp_step(999, 3), 0);
+    assert_eq!(ramp_step(2500, 23), 4);
+    assert_eq!(ramp_step(2500, 12), 2, "shrinking the worm eases the ramp off");
+    let obstacles = sim.state.level.obstacles.len();
+    sim.state.score = 2000;
+    sim.tick();
+    assert_eq!(sim.state.hazards, 4);
+    assert_eq!(sim.state.level.obstacles.len(), obstacles + 4);
+    assert!((sim.state.difficulty_speed() - 1.36).abs() < 1e-4);
+    assert!((sim.state.base_stats.move_interval / sim.state.player.move_interval - 1.36).abs() < 1e-4);
+    let definition = Difficulty::Hard.definition();
+    assert!(definition.crate_chance(5) < definition.crate_chance(0));
+    assert_eq!(definition.hazards(100), definition.max_hazards);
+
+    // Every preset gets harder than the one before, and Easy never adds asteroids
+    for pair in DIFFICULTIES.windows(2) {
+        assert!(pair[1].speed(0) > pair[0].speed(0));
+        assert!(pair[1].max_hazards > pair[0].max_hazards);
+    }
+    assert_eq!(Difficulty::Easy.definition().hazards(10), 0);
+
+    // Normal starts out as the classic game and ramps up from there
+    let normal = Difficulty::Normal.definition();
+    assert_eq!((normal.speed(0), normal.crate_chance(0), normal.hazards(0)), (1.0, LOOT_CRATE_SPAWN_CHANCE as u32, 0));
+    assert!(normal.speed(3) > 1.0 && normal.crate_chance(3) < normal.crate_chance(0) && normal.hazards(3) > 0);
+
+    // The asteroids survive a save, and a restart clears them
+    let mut resumed = new_game();
+    resumed.load_state(&sim.save_state()).unwrap();
+    assert_eq!(resumed.state.config.difficulty, Difficulty::Hard);
+    assert_eq!(resumed.state.level.obstacles, sim.state.level.obstacles);
+    sim.restart();
+    assert_eq!((sim.state.hazards, sim.state.level.obstacles.len()), (0, obstacles));
+
+    // Ending a run (here by flying into the wall) records the score for its preset only
+    sim.state.score = 700;
+    let mut events = Vec::new();
+    while !sim.state.game_over {
+        sim.tick();
+        events.extend(sim.drain_events());
+    }
+    assert!(events.contains(&SimulationEvent::NewHighScore(Difficulty::Hard)));
+    assert_eq!(sim.high_score(Difficulty::Hard), sim.state.score);
+    assert_eq!(sim.high_score(Difficulty::Normal), 0);
+
+    // Campaign stages keep their own pace, crate odds and layout whatever the preset
+    assert!(sim.start_campaign(0));
+    sim.state.score = 2000;
+    sim.tick();
+    assert_eq!(sim.state.difficulty_speed(), 1.0);
+    assert_eq!(sim.state.player.move_interval, sim.state.base_stats.move_interval);
+    assert_eq!(difficulty::crate_chance(&sim.state), LOOT_CRATE_SPAWN_CHANCE as u32);
+    assert_eq!(sim.state.hazards, 0);
+
+    // ...and a replay of a campaign run knows its stage, so it plays out the same
+    let mut campaign = Simulation::with_config(ManualClock::new(0.0), 7, hard);
+    assert!(campaign.start_campaign(0));
+    for key in ["KeyS", "KeyD", "KeyW", "KeyD"] {
+        run_moves(&mut campaign, 12);
+        campaign.handle_key_down(key);
+    }
+    while !campaign.state.game_over && campaign.state.run_ticks() < 6000 {
+        campaign.tick();
+    }
+    let replay = Replay::from_bytes(&campaign.export_replay().to_bytes()).unwrap();
+    assert_eq!(replay.campaign_stage, Some(0));
+    let outcome = Simulation::run_replay(&replay);
+    assert_eq!((outcome.score, outcome.ticks), (replay.score, replay.ticks));
+}